@@ -15,8 +15,62 @@ interface Product {
     id: string,
     name: string,
     description: Option<string>,
-    price: number,
-    image: Option<string>,
+    price: Money,
+    images: ProductImage[],
+    stock: number,
+    available: boolean,
+    average_stars: Option<number>,
+    review_count: number,
+}
+
+/**
+ * An amount of money, `cents` is the raw value in minor units
+ */
+interface Money {
+    cents: number,
+    currency: string,
+    formatted: string
+}
+
+/**
+ * An image of the gallery of a product, `url` is relative to the host of the API
+ */
+interface ProductImage {
+    hash: string,
+    alt: Option<string>,
+    position: number,
+    url: string,
+    sizes: ImageSize[]
+}
+
+/**
+ * A resized version of an image, never wider than the original one
+ */
+interface ImageSize {
+    width: number,
+    url: string,
+    webp_url: string
+}
+
+/**
+ * A purchasable version of a product, `values` gives the value of each option, e.g. `{"Taille": "M"}`
+ */
+interface ProductVariant {
+    id: string,
+    product: string,
+    sku: Option<string>,
+    price_override: Option<number>,
+    price: Money,
+    stock: number,
+    values: Record<string, string>
+}
+
+/**
+ * The options of a product with their values in display order, and all its variants
+ */
+interface ProductVariants {
+    options: { name: string, values: string[] }[],
+    variants: ProductVariant[]
 }
 
 interface ProductCharacteristic {
@@ -31,7 +85,30 @@ interface Review {
     user: string,
     review: string,
     stars: number,
-    username: string
+    username: string,
+    created_at: string,
+    status: "pending" | "approved" | "rejected",
+    rejection_reason: Option<string>
+}
+
+/**
+ * Distribution of the stars given to a product, `histogram` goes from 5 stars down to 1
+ */
+interface ReviewSummary {
+    total: number,
+    average: Option<number>,
+    histogram: { stars: number, count: number, percentage: number }[]
+}
+
+/**
+ * A page of a paginated listing, `next_cursor` is null on the last page
+ */
+interface Page<T> {
+    items: T[],
+    page: Option<number>,
+    page_size: number,
+    total: number,
+    next_cursor: Option<string>
 }
 
 type Option<T> = T | null;
@@ -71,11 +148,11 @@ class PedalizeApi {
      *
      * @param {number} page - The page number to retrieve. Must be a positive or null integer.
      *
-     * @returns {Promise<Option<Product[]>>} - A promise that resolves to an Option containing an array of Product objects on success, or an error on failure.
+     * @returns {Promise<Option<Page<Product>>>} - A promise that resolves to an Option containing a page of Product objects on success, or an error on failure.
      *
      * @throws {Error} - If the page number is not a positive or null integer.
      */
-    async get_page(page: number): Promise<Option<Product[]>> {
+    async get_page(page: number): Promise<Option<Page<Product>>> {
         if (page < 0 && Math.floor(page) == page)
             throw new Error("The page must be a positive or null integer")
 
@@ -132,16 +209,36 @@ class PedalizeApi {
         }
     }
 
+    /**
+     * Retrieves the options and the variants of a product specified by its ID.
+     *
+     * @param {string} id - The ID of the product.
+     * @returns {Promise<Option<ProductVariants>>} - A promise that resolves to the options and the variants, or an error.
+     */
+    async get_product_variants(id: string): Promise<Option<ProductVariants>> {
+        const req = new RequestBuilder(`${this.config.host}/product/${id}/variants`);
+        req.set_method("GET");
+
+        try {
+            const res = await req.send();
+            return await res.json();
+
+        } catch (error) {
+            console.error(error);
+            return error;
+        }
+    }
+
     /**
      * Retrieves the reviews for a given product ID.
      *
      * @param {string} id - The ID of the product to retrieve reviews for.
      *
-     * @returns {Promise<Option<Review[]>>} - A promise that resolves to an optional array of Review objects.
-     *       The array will be empty if no reviews are found.
+     * @returns {Promise<Option<Page<Review>>>} - A promise that resolves to an optional page of Review objects, the most recent first.
+     *       Its items will be empty if no reviews are found.
      *       If an error occurs during the retrieval process, the promise will reject with the error.
      */
-    async get_reviews(id: string): Promise<Option<Review[]>> {
+    async get_reviews(id: string): Promise<Option<Page<Review>>> {
         const req = new RequestBuilder(`${this.config.host}/product/${id}/reviews`);
         req.set_method("GET");
 
@@ -155,6 +252,49 @@ class PedalizeApi {
         }
     }
 
+    /**
+     * Retrieves the number of reviews per star value of a product, their mean and their total.
+     *
+     * @param {string} id - The ID of the product.
+     *
+     * @returns {Promise<Option<ReviewSummary>>} - A promise that resolves to the summary, or an error.
+     */
+    async get_review_summary(id: string): Promise<Option<ReviewSummary>> {
+        const req = new RequestBuilder(`${this.config.host}/product/${id}/reviews/summary`);
+        req.set_method("GET");
+
+        try {
+            const res = await req.send();
+            return await res.json();
+
+        } catch (error) {
+            console.error(error);
+            return error;
+        }
+    }
+
+
+    /**
+     * Chooses a new password with the token of a reset link.
+     *
+     * @param {string} token - The token given by the reset link.
+     * @param {string} password - The new password.
+     *
+     * @returns {Promise<Object>} - A promise that resolves to the response of the server, or an error.
+     */
+    async reset_password(token: string, password: string): Promise<Object> {
+        const req = new RequestBuilder(`${this.config.host}/clients/password/reset`);
+        req.set_method("POST");
+        req.set_body(new URLSearchParams({ token, password }));
+
+        try {
+            const res = await req.send();
+            return await res.json();
+        } catch (error) {
+            console.error(error);
+            return error;
+        }
+    }
 
     /**
      * Retrieves the shopping cart from the server.
@@ -177,12 +317,13 @@ class PedalizeApi {
     }
 
 
-    async add_article_to_cart(id: string): Promise<Object> {
+    async add_article_to_cart(id: string, variant?: string): Promise<Object> {
         const req = new RequestBuilder(`${this.config.host}/shopping_cart/articles/add`);
         req.set_method("POST");
         req.set_body(
             new URLSearchParams({
-                product: id
+                product: id,
+                ...(variant ? { variant } : {})
             })
         );
 
@@ -195,12 +336,13 @@ class PedalizeApi {
         }
     }
 
-    async remove_cart_article(id: string): Promise<string> {
+    async remove_cart_article(id: string, variant?: string): Promise<string> {
         const req = new RequestBuilder(`${this.config.host}/shopping_cart/articles/remove`);
         req.set_method("DELETE");
         req.set_body(
             new URLSearchParams({
-                product: id
+                product: id,
+                ...(variant ? { variant } : {})
             })
         );
         //req.add_header("Content-Type:", "application/x-www-form-urlencoded")
@@ -285,6 +427,8 @@ class RequestBuilder {
         let config: RequestInit = {
             method: this.method || "GET",
             body: this.body || null,
+            // send the session and guest cookies to the API
+            credentials: "include",
         };
         if (this.headers) {
             config.headers = this.headers
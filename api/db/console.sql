@@ -1,14 +1,15 @@
 SELECT * FROM product;
 
-UPDATE product
-SET
-    main_image = NULL
+DELETE FROM product_image
 WHERE
-    id != '502f846e-0641-45c0-9244-42fac8f5bfda';
+    product != '502f846e-0641-45c0-9244-42fac8f5bfda';
 
-UPDATE product SET price = 188 WHERE id = '502f846e-0641-45c0-9244-42fac8f5bfda';
+UPDATE product SET price = 18800 WHERE id = '502f846e-0641-45c0-9244-42fac8f5bfda';
 
-SELECT name, product.main_image FROM product;
+SELECT product.name, product_image.hash, product_image.extension
+FROM product
+LEFT JOIN product_image ON product_image.product = product.id
+ORDER BY product.name, product_image.position;
 
 INSERT INTO product_characteristic (name, detail, product) VALUES
                                                                ('Cadre Ransom Alloy SL',
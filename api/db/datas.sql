@@ -1,16 +1,25 @@
 DELETE FROM shopping_cart_article;
 DELETE FROM reviews;
 DELETE FROM product_characteristic;
+DELETE FROM product_image;
 DELETE FROM product;
 
 
-INSERT INTO product (id, name, description, main_image) VALUE (
+INSERT INTO product (id, name, description, price) VALUE (
     '502f846e-0641-45c0-9244-42fac8f5bfda',
     'Casque Crossframe Pro',
     'Le tout nouveau casque Crossframe Pro est destiné aux amateurs de sensations fortes, de sprints endiablés, et de tout ce qui s\'en rapproche. Léger, polyvalent, et muni des technologies MIPS® et BOA®, ce casque se tient prêt pour toutes les rides dans lesquelles vous embarquerez.',
-    'casque_vtt_1.png'
+    18800 -- 188,00 €, le prix est en centimes
 );
 
+-- views/static/img/casque_vtt_1.png, à copier dans le dossier `image_dir` sous le nom de son empreinte (voir le README)
+INSERT INTO product_image (product, hash, extension, alt, position) VALUE
+    ('502f846e-0641-45c0-9244-42fac8f5bfda', '6fe411592a3073e19e14dee25caeb46590d75f0628024966c67fd36eadc410ab', 'png', 'Casque Crossframe Pro', 0);
+
+-- Sa déclinaison par défaut, sans option
+INSERT INTO product_variant (id, product, stock) VALUE
+    (UUID(), '502f846e-0641-45c0-9244-42fac8f5bfda', 20);
+
 
 INSERT INTO product_characteristic (name, detail, product) VALUES (
         'Système de protection MIPS®',
@@ -1,59 +1,186 @@
+DROP TABLE IF EXISTS password_resets;
+DROP TABLE IF EXISTS sessions;
 DROP TABLE IF EXISTS shopping_cart_article;
 DROP TABLE IF EXISTS shopping_cart;
+DROP TABLE IF EXISTS product_variant_value;
+DROP TABLE IF EXISTS product_variant;
+DROP TABLE IF EXISTS variant_option_value;
+DROP TABLE IF EXISTS variant_option;
 DROP TABLE IF EXISTS reviews;
 DROP TABLE IF EXISTS product_characteristic;
+DROP TABLE IF EXISTS product_image;
+DROP TABLE IF EXISTS product_category;
+DROP TABLE IF EXISTS category;
 DROP TABLE IF EXISTS product;
 DROP TABLE IF EXISTS clients;
 
 -- Les clients enregistrés
 CREATE OR REPLACE TABLE clients (
     id CHAR(36) NOT NULL,
-    token CHAR(64) NOT NULL,
-    passwd CHAR(64) NOT NUll,
+    passwd VARCHAR(255) NOT NUll, -- Chaîne PHC Argon2id, ou ancienne empreinte SHA-256 en hexadécimal
     username VARCHAR(32),
+    email VARCHAR(254),
+    role ENUM('customer', 'staff', 'admin') NOT NULL DEFAULT 'customer',
 
+    UNIQUE (username),
+    UNIQUE (email),
     PRIMARY KEY (id)
 );
 
+-- Les sessions ouvertes des clients, seule l'empreinte SHA-256 du jeton est stockée
+CREATE OR REPLACE TABLE sessions (
+    id CHAR(36) NOT NULL,
+    token_hash CHAR(64) NOT NULL,
+    client CHAR(36) NOT NULL,
+    created_at DATETIME NOT NULL,
+    expires_at DATETIME NOT NULL,
+    last_seen DATETIME NOT NULL,
+    user_agent VARCHAR(256),
+
+    UNIQUE (token_hash),
+    INDEX (client),
+    FOREIGN KEY (client) REFERENCES clients (id) ON DELETE CASCADE,
+    PRIMARY KEY (id)
+);
+
+-- Les jetons à usage unique de réinitialisation des mots de passe, seule leur empreinte SHA-256 est stockée
+CREATE OR REPLACE TABLE password_resets (
+    token_hash CHAR(64) NOT NULL,
+    client CHAR(36) NOT NULL,
+    created_at DATETIME NOT NULL,
+    expires_at DATETIME NOT NULL,
+    used_at DATETIME,
+
+    FOREIGN KEY (client) REFERENCES clients (id) ON DELETE CASCADE,
+    PRIMARY KEY (token_hash)
+);
+
 -- Contient les produits
 CREATE OR REPLACE TABLE product (
     id CHAR(36),
-    name VARCHAR(256) NOT NULL,
-    description VARCHAR(2048),
-    price INT UNSIGNED NOT NULL DEFAULT 1,
+    -- La collation unicode_ci rend la recherche insensible à la casse et aux accents
+    name VARCHAR(256) CHARACTER SET utf8mb4 COLLATE utf8mb4_unicode_ci NOT NULL,
+    description VARCHAR(2048) CHARACTER SET utf8mb4 COLLATE utf8mb4_unicode_ci,
+    price BIGINT UNSIGNED NOT NULL DEFAULT 1, -- En centimes
+    currency CHAR(3) NOT NULL DEFAULT 'EUR', -- Code ISO 4217
 
-    main_image VARCHAR(256), -- Can be null :)
-    second_image VARCHAR(256), -- Can be null :)
-    third_image VARCHAR(256), -- Can be null :)
-    fourth_image VARCHAR(256), -- Can be null :)
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
 
-    size TINYINT NOT NULL DEFAULT 0,
-    wheel_size TINYINT NOT NULL DEFAULT 0,
-
-    UNIQUE (main_image, second_image, third_image, fourth_image),
+    FULLTEXT (name, description),
     CHECK (price > 0),
     PRIMARY KEY (id)
 );
 
+-- La galerie d'images d'un produit, dans l'ordre d'affichage
+CREATE OR REPLACE TABLE product_image (
+    product CHAR(36) NOT NULL,
+    hash CHAR(64) NOT NULL, -- Empreinte SHA-256 du contenu, le fichier est stocké sous ce nom
+    extension VARCHAR(4) NOT NULL, -- png, jpg ou webp
+    alt VARCHAR(256), -- Texte alternatif
+    position INT UNSIGNED NOT NULL DEFAULT 0,
+
+    INDEX (hash),
+    FOREIGN KEY (product) REFERENCES product (id) ON DELETE CASCADE,
+    PRIMARY KEY (product, hash)
+);
+
 -- Contiendra toutes les caractéristiques d'un produit
 CREATE OR REPLACE TABLE product_characteristic (
     name VARCHAR(128),
-    detail VARCHAR(256),
+    detail VARCHAR(256) CHARACTER SET utf8mb4 COLLATE utf8mb4_unicode_ci,
     product CHAR(36),
+    position INT UNSIGNED NOT NULL DEFAULT 0, -- Ordre d'affichage des caractéristiques
 
+    FULLTEXT (detail),
     FOREIGN KEY (product) REFERENCES product (id) ON DELETE CASCADE,
     PRIMARY KEY (product, name)
 );
 
+-- Les options dans lesquelles un produit est vendu (taille du cadre, diamètre des roues, couleur...)
+CREATE OR REPLACE TABLE variant_option (
+    id CHAR(36) NOT NULL,
+    product CHAR(36) NOT NULL,
+    name VARCHAR(64) NOT NULL,
+    position INT UNSIGNED NOT NULL DEFAULT 0,
+
+    UNIQUE (product, name),
+    FOREIGN KEY (product) REFERENCES product (id) ON DELETE CASCADE,
+    PRIMARY KEY (id)
+);
+
+-- Les valeurs possibles d'une option (S, M, L, XL...)
+CREATE OR REPLACE TABLE variant_option_value (
+    id CHAR(36) NOT NULL,
+    option_id CHAR(36) NOT NULL,
+    value VARCHAR(64) NOT NULL,
+    position INT UNSIGNED NOT NULL DEFAULT 0,
+
+    UNIQUE (option_id, value),
+    FOREIGN KEY (option_id) REFERENCES variant_option (id) ON DELETE CASCADE,
+    PRIMARY KEY (id)
+);
+
+-- Les déclinaisons achetables d'un produit, chaque produit en a au moins une
+CREATE OR REPLACE TABLE product_variant (
+    id CHAR(36) NOT NULL,
+    product CHAR(36) NOT NULL,
+    sku VARCHAR(64),
+    price BIGINT UNSIGNED, -- En centimes, NULL pour utiliser le prix du produit
+    stock INT UNSIGNED NOT NULL DEFAULT 0, -- Quantité disponible
+
+    UNIQUE (sku),
+    CHECK (price > 0),
+    FOREIGN KEY (product) REFERENCES product (id) ON DELETE CASCADE,
+    PRIMARY KEY (id)
+);
+
+-- La valeur de chaque option d'une déclinaison
+CREATE OR REPLACE TABLE product_variant_value (
+    variant CHAR(36) NOT NULL,
+    value CHAR(36) NOT NULL,
+
+    FOREIGN KEY (variant) REFERENCES product_variant (id) ON DELETE CASCADE,
+    FOREIGN KEY (value) REFERENCES variant_option_value (id) ON DELETE CASCADE,
+    PRIMARY KEY (variant, value)
+);
+
+-- Contient les catégories des produits, sous forme d'arbre
+CREATE OR REPLACE TABLE category (
+    id CHAR(36) NOT NULL,
+    slug VARCHAR(64) NOT NULL, -- Identifiant de la catégorie dans les urls
+    name VARCHAR(128) NOT NULL,
+    parent CHAR(36), -- NULL pour une catégorie racine
+    position INT UNSIGNED NOT NULL DEFAULT 0, -- Ordre d'affichage parmi les catégories du même parent
+
+    UNIQUE (slug),
+    FOREIGN KEY (parent) REFERENCES category (id),
+    PRIMARY KEY (id)
+);
+
+-- Associe les produits à leurs catégories
+CREATE OR REPLACE TABLE product_category (
+    product CHAR(36) NOT NULL,
+    category CHAR(36) NOT NULL,
+
+    FOREIGN KEY (product) REFERENCES product (id) ON DELETE CASCADE,
+    FOREIGN KEY (category) REFERENCES category (id) ON DELETE CASCADE,
+    PRIMARY KEY (product, category)
+);
+
 -- Contient les avis d'un produit
 CREATE OR REPLACE TABLE reviews (
     id CHAR(36) NOT NULL,
     product CHAR(36) NOT NULL,
     user CHAR(36),
     review VARCHAR(512),
-    stars TINYINT UNSIGNED NOT NULL DEFAULT 0,
+    stars TINYINT UNSIGNED NOT NULL, -- De 1 à 5
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    status ENUM('pending', 'approved', 'rejected') NOT NULL DEFAULT 'pending', -- Seuls les avis approuvés sont publiés
+    rejection_reason VARCHAR(256), -- Motif donné par le modérateur qui a rejeté l'avis
 
-    CHECK (0 <= stars <= 5),
+    INDEX (status, created_at), -- File de modération
+    UNIQUE (user, product), -- Un seul avis par client et par produit
+    CHECK (stars BETWEEN 1 AND 5),
     FOREIGN KEY (user) REFERENCES clients (id) ON DELETE CASCADE,
     FOREIGN KEY (product) REFERENCES product (id) ON DELETE CASCADE,
     PRIMARY KEY (id)
@@ -64,6 +191,7 @@ CREATE OR REPLACE TABLE reviews (
 CREATE OR REPLACE TABLE shopping_cart (
     user CHAR(36) NOT NULL,
     id CHAR(36) NOT NUll,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP, -- Le panier d'un invité est supprimé quand son cookie expire
 
     UNIQUE (user),
     PRIMARY KEY (id)
@@ -72,13 +200,13 @@ CREATE OR REPLACE TABLE shopping_cart (
 -- Contient les articles avec leur quantité des paniers
 CREATE OR REPLACE TABLE shopping_cart_article (
     shopping_cart_id CHAR(36) NOT NULL,
-    product CHAR(36) NOT NULL,
+    variant CHAR(36) NOT NULL,
     quantity INT UNSIGNED DEFAULT 1,
 
-    FOREIGN KEY (product) REFERENCES product (id) ON DELETE CASCADE,
+    FOREIGN KEY (variant) REFERENCES product_variant (id) ON DELETE CASCADE,
     FOREIGN KEY (shopping_cart_id) REFERENCES shopping_cart (id) ON DELETE CASCADE,
     CHECK (quantity > 0),
-    PRIMARY KEY (shopping_cart_id, product)
+    PRIMARY KEY (shopping_cart_id, variant)
 );
 
 
@@ -89,9 +217,8 @@ CREATE OR REPLACE TABLE shopping_cart_article (
 
 
 
-INSERT INTO clients VALUE (
+INSERT INTO clients (id, passwd, username) VALUE (
     '251ef68e-6d10-4e08-a8e9-d4f42ad9fe36',
-    'cd251870133e6dc175f6f616ea1a6e0dde6601dbde6e2ec173d1cc720cac7b58',
     '909104cdb5b06af2606ed4a197b07d09d5ef9a4aad97780c2fe48053bce2be52', -- "yeet"
     'Test'
 );
@@ -100,23 +227,74 @@ INSERT INTO product (id, name, description, price) VALUE (
     '30dfd1d3-76bd-4c3b-b988-dd8235f7f238',
     'Kit anti-crevaison',
     'Kit anti-crevaison pour vélo',
-    10
-);
-INSERT INTO product (id, name, description, price, main_image) VALUES
-    ('d2c0b25c-e39a-43d4-8f06-ce5ed2d7919c', 'Casque de cyclisme', 'Casque léger et aérodynamique pour une protection optimale lors de vos sorties à vélo.', 79.99, 'casque_cyclisme.jpg'),
-    ('dcfd55fd-5891-41a8-a5b4-9ee4ff00a576', 'Maillot de cyclisme', 'Maillot respirant en tissu technique pour une performance maximale sur la route.', 49.99, 'maillot_cyclisme.jpg'),
-    ('7a555048-64ae-4089-82de-2d9db6034e19', 'Pneus de vélo de route', 'Pneus haute performance offrant une adhérence exceptionnelle sur les routes asphaltées.', 29.99, 'pneus_route.jpg'),
-    ('3678c5c0-db86-4ef9-8bdd-c8b96affe2f7', 'Short de cyclisme', 'Short rembourré pour plus de confort pendant de longues balades à vélo.', 39.99, 'short_cyclisme.jpg'),
-    ('19a13f5d-8530-4da4-a1b8-ef42279dcffb', 'Gants de cyclisme', 'Gants ergonomiques avec rembourrage pour une meilleure prise en main du guidon.', 19.99, 'gants_cyclisme.jpg'),
-    ('749c5198-b498-4981-9e48-b95123a88775', 'Lunettes de soleil pour cyclistes', 'Lunettes polarisées pour protéger vos yeux des rayons UV pendant vos sorties en plein air.', 29.99, 'lunettes_cyclistes.jpg'),
-    ('f0e22302-db5c-4277-b890-9291ca89abb3', 'Sac à dos de cyclisme', 'Sac à dos léger et compact pour transporter vos affaires essentielles pendant vos trajets à vélo.', 49.99, 'sac_a_dos_cyclisme.jpg'),
-    ('b3343d89-d275-435f-be8e-0d69ae186f9f', 'Bidon d\'eau de cyclisme', 'Bidon de 750 ml avec une valve anti-fuite pour vous hydrater pendant vos sorties.', 9.99, 'bidon_cyclisme.jpg'),
-    ('97274f94-849f-4e01-b706-0374d06e30dc', 'Cadenas de vélo', 'Cadenas en acier durable pour protéger votre vélo contre le vol.', 19.99, 'cadenas_velo.jpg'),
-    ('32559cd1-cb95-4a69-bde1-66fd2b7e3212', 'Éclairage de vélo LED', 'Ensemble d\'éclairage avant et arrière pour assurer votre visibilité pendant vos sorties nocturnes.', 14.99, 'eclairage_velo.jpg');
+    1000
+);
+INSERT INTO product (id, name, description, price) VALUES
+    ('d2c0b25c-e39a-43d4-8f06-ce5ed2d7919c', 'Casque de cyclisme', 'Casque léger et aérodynamique pour une protection optimale lors de vos sorties à vélo.', 7999),
+    ('dcfd55fd-5891-41a8-a5b4-9ee4ff00a576', 'Maillot de cyclisme', 'Maillot respirant en tissu technique pour une performance maximale sur la route.', 4999),
+    ('7a555048-64ae-4089-82de-2d9db6034e19', 'Pneus de vélo de route', 'Pneus haute performance offrant une adhérence exceptionnelle sur les routes asphaltées.', 2999),
+    ('3678c5c0-db86-4ef9-8bdd-c8b96affe2f7', 'Short de cyclisme', 'Short rembourré pour plus de confort pendant de longues balades à vélo.', 3999),
+    ('19a13f5d-8530-4da4-a1b8-ef42279dcffb', 'Gants de cyclisme', 'Gants ergonomiques avec rembourrage pour une meilleure prise en main du guidon.', 1999),
+    ('749c5198-b498-4981-9e48-b95123a88775', 'Lunettes de soleil pour cyclistes', 'Lunettes polarisées pour protéger vos yeux des rayons UV pendant vos sorties en plein air.', 2999),
+    ('f0e22302-db5c-4277-b890-9291ca89abb3', 'Sac à dos de cyclisme', 'Sac à dos léger et compact pour transporter vos affaires essentielles pendant vos trajets à vélo.', 4999),
+    ('b3343d89-d275-435f-be8e-0d69ae186f9f', 'Bidon d\'eau de cyclisme', 'Bidon de 750 ml avec une valve anti-fuite pour vous hydrater pendant vos sorties.', 999),
+    ('97274f94-849f-4e01-b706-0374d06e30dc', 'Cadenas de vélo', 'Cadenas en acier durable pour protéger votre vélo contre le vol.', 1999),
+    ('32559cd1-cb95-4a69-bde1-66fd2b7e3212', 'Éclairage de vélo LED', 'Ensemble d\'éclairage avant et arrière pour assurer votre visibilité pendant vos sorties nocturnes.', 1499);
+
+-- Une déclinaison par défaut pour chaque produit, sauf le casque vendu en plusieurs tailles
+INSERT INTO product_variant (id, product, stock)
+    SELECT UUID(), id, 20 FROM product WHERE id != 'd2c0b25c-e39a-43d4-8f06-ce5ed2d7919c';
+
+INSERT INTO variant_option (id, product, name, position) VALUE
+    ('0c7e4f2a-9b31-4d6e-8a15-3f2b7c9d1e40', 'd2c0b25c-e39a-43d4-8f06-ce5ed2d7919c', 'Taille', 0);
+
+INSERT INTO variant_option_value (id, option_id, value, position) VALUES
+    ('6a1d3e5f-7b29-4c48-9e0a-1b2c3d4e5f60', '0c7e4f2a-9b31-4d6e-8a15-3f2b7c9d1e40', 'S', 0),
+    ('7b2e4f60-8c3a-4d59-af1b-2c3d4e5f6071', '0c7e4f2a-9b31-4d6e-8a15-3f2b7c9d1e40', 'M', 1),
+    ('8c3f5071-9d4b-4e6a-b02c-3d4e5f607182', '0c7e4f2a-9b31-4d6e-8a15-3f2b7c9d1e40', 'L', 2);
+
+INSERT INTO product_variant (id, product, sku, stock) VALUES
+    ('9d406182-ae5c-4f7b-813d-4e5f60718293', 'd2c0b25c-e39a-43d4-8f06-ce5ed2d7919c', 'CASQUE-S', 5),
+    ('ae517293-bf6d-408c-924e-5f60718293a4', 'd2c0b25c-e39a-43d4-8f06-ce5ed2d7919c', 'CASQUE-M', 10),
+    ('bf6283a4-c07e-419d-a35f-60718293a4b5', 'd2c0b25c-e39a-43d4-8f06-ce5ed2d7919c', 'CASQUE-L', 0);
+
+INSERT INTO product_variant_value (variant, value) VALUES
+    ('9d406182-ae5c-4f7b-813d-4e5f60718293', '6a1d3e5f-7b29-4c48-9e0a-1b2c3d4e5f60'),
+    ('ae517293-bf6d-408c-924e-5f60718293a4', '7b2e4f60-8c3a-4d59-af1b-2c3d4e5f6071'),
+    ('bf6283a4-c07e-419d-a35f-60718293a4b5', '8c3f5071-9d4b-4e6a-b02c-3d4e5f607182');
+
+-- Images de views/static/img, à copier dans le dossier `image_dir` sous le nom de leur empreinte (voir le README)
+INSERT INTO product_image (product, hash, extension, alt, position) VALUES
+    ('3678c5c0-db86-4ef9-8bdd-c8b96affe2f7', 'd9fb97af44adc51d7981f074d03436e6f3d6abe58b8f3bf4e8113ec36b434e04', 'webp', 'Short de cyclisme', 0),
+    ('19a13f5d-8530-4da4-a1b8-ef42279dcffb', '9b4d8b9110721f0fa68e89f096f34c56c74a0a8f77278e9898f0d43e4571b0a0', 'webp', 'Gants de cyclisme', 0),
+    ('749c5198-b498-4981-9e48-b95123a88775', '24be5cc53f5a1fd1a5a755a0b9a0b9468e5d172af027e1011e1be3b8a331f2a6', 'jpg', 'Lunettes de soleil pour cyclistes', 0),
+    ('f0e22302-db5c-4277-b890-9291ca89abb3', '8050da4f02dda4a39ebb5c0fd4395366456010d694550b54a6a6b572ad06c5d1', 'webp', 'Sac à dos de cyclisme', 0),
+    ('b3343d89-d275-435f-be8e-0d69ae186f9f', '9c573a2701095aae840cfae0377dd3e52fef2a40c83fca01e12222e26658d05e', 'jpg', 'Bidon d\'eau de cyclisme', 0),
+    ('97274f94-849f-4e01-b706-0374d06e30dc', '6406deb5686bac6c1c6358eb58ab06816a6201af0dc4b7deab9ec07441cb3760', 'jpg', 'Cadenas de vélo', 0),
+    ('32559cd1-cb95-4a69-bde1-66fd2b7e3212', '7ba35090c65aa06f68e778113f1967f2e273e5fb42c516628ad70c472055d6ff', 'jpg', 'Éclairage de vélo LED', 0);
 
 INSERT INTO product_characteristic (product, name, detail) VALUE
     ('f0e22302-db5c-4277-b890-9291ca89abb3', 'Capacité', '40L');
 
+INSERT INTO category (id, slug, name, parent, position) VALUES
+    ('5b0f2c1e-0d5e-4a5f-9a3c-6f1de0c4b8a1', 'equipement', 'Équipement', NULL, 0),
+    ('a4c3e7d2-8f61-4b0a-b2d5-3e9f7c1a6d40', 'vetements', 'Vêtements', NULL, 1),
+    ('c8e1b5f3-2a47-4d9c-8e06-7b3f5a2d9c14', 'pieces', 'Pièces et accessoires', NULL, 2),
+    ('e2d7a9c4-5b13-4f8e-a6c0-1d4b8e3f7a25', 'protection', 'Protection', '5b0f2c1e-0d5e-4a5f-9a3c-6f1de0c4b8a1', 0);
+
+INSERT INTO product_category (product, category) VALUES
+    ('d2c0b25c-e39a-43d4-8f06-ce5ed2d7919c', 'e2d7a9c4-5b13-4f8e-a6c0-1d4b8e3f7a25'),
+    ('749c5198-b498-4981-9e48-b95123a88775', 'e2d7a9c4-5b13-4f8e-a6c0-1d4b8e3f7a25'),
+    ('19a13f5d-8530-4da4-a1b8-ef42279dcffb', 'a4c3e7d2-8f61-4b0a-b2d5-3e9f7c1a6d40'),
+    ('dcfd55fd-5891-41a8-a5b4-9ee4ff00a576', 'a4c3e7d2-8f61-4b0a-b2d5-3e9f7c1a6d40'),
+    ('3678c5c0-db86-4ef9-8bdd-c8b96affe2f7', 'a4c3e7d2-8f61-4b0a-b2d5-3e9f7c1a6d40'),
+    ('f0e22302-db5c-4277-b890-9291ca89abb3', '5b0f2c1e-0d5e-4a5f-9a3c-6f1de0c4b8a1'),
+    ('b3343d89-d275-435f-be8e-0d69ae186f9f', '5b0f2c1e-0d5e-4a5f-9a3c-6f1de0c4b8a1'),
+    ('32559cd1-cb95-4a69-bde1-66fd2b7e3212', 'c8e1b5f3-2a47-4d9c-8e06-7b3f5a2d9c14'),
+    ('97274f94-849f-4e01-b706-0374d06e30dc', 'c8e1b5f3-2a47-4d9c-8e06-7b3f5a2d9c14'),
+    ('7a555048-64ae-4089-82de-2d9db6034e19', 'c8e1b5f3-2a47-4d9c-8e06-7b3f5a2d9c14'),
+    ('30dfd1d3-76bd-4c3b-b988-dd8235f7f238', 'c8e1b5f3-2a47-4d9c-8e06-7b3f5a2d9c14');
+
 
 # SELECT
 #     reviews.id AS id,
@@ -0,0 +1,483 @@
+use serde::{Deserialize, Serialize};
+use sqlx::MySqlPool;
+use crate::constants::{CATEGORY_NAME_MAX_LENGTH, CATEGORY_SLUG_MAX_LENGTH};
+use crate::declaration::ApiError;
+
+pub(crate) type CategoryId = String;
+
+#[derive(sqlx::FromRow, Serialize, Deserialize, Clone)]
+pub(crate) struct Category {
+    pub id: CategoryId,
+    /// Identifier of the category in the urls, e.g. `casques`
+    pub slug: String,
+    pub name: String,
+    pub parent: Option<CategoryId>,
+    /// The sub-categories of a category are displayed by ascending position
+    pub position: u32
+}
+
+/// A category with all its sub-categories
+#[derive(Serialize, Deserialize, Clone)]
+pub(crate) struct CategoryNode {
+    #[serde(flatten)]
+    pub category: Category,
+    pub children: Vec<CategoryNode>
+}
+
+impl Category {
+    async fn get_all(pool: &MySqlPool) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>("SELECT * FROM category ORDER BY position, name")
+            .fetch_all(pool)
+            .await
+    }
+
+    async fn find_by_slug(pool: &MySqlPool, slug: &str) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>("SELECT * FROM category WHERE slug = ?")
+            .bind(slug)
+            .fetch_optional(pool)
+            .await
+    }
+
+    /// Build the category tree, the roots are the categories without parent
+    fn tree(categories: Vec<Self>) -> Vec<CategoryNode> {
+        fn children_of(parent: Option<&str>, categories: &[Category]) -> Vec<CategoryNode> {
+            categories.iter()
+                .filter(|c| c.parent.as_deref() == parent)
+                .map(|c| CategoryNode {
+                    category: c.clone(),
+                    children: children_of(Some(c.id.as_str()), categories)
+                })
+                .collect()
+        }
+
+        children_of(None, categories.as_slice())
+    }
+
+    /// Check the category against the constraints of the `category` table
+    fn validate(&self) -> Result<(), ApiError> {
+        if self.name.is_empty() || self.name.chars().count() > CATEGORY_NAME_MAX_LENGTH {
+            return Err(ApiError::new(7004, format!("The name must contain between 1 and {CATEGORY_NAME_MAX_LENGTH} characters")))
+        }
+
+        let valid_slug = self.slug.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+        if !valid_slug || self.slug.is_empty() || self.slug.len() > CATEGORY_SLUG_MAX_LENGTH {
+            return Err(ApiError::new(
+                7005,
+                format!("The slug must contain between 1 and {CATEGORY_SLUG_MAX_LENGTH} lowercase letters, digits or dashes")
+            ))
+        }
+
+        Ok(())
+    }
+
+    /// Whether `ancestor` is the category itself or one of its parents, used to refuse a cycle in the tree
+    async fn is_descendant_of(pool: &MySqlPool, category: &str, ancestor: &str) -> Result<bool, sqlx::Error> {
+        sqlx::query_scalar::<_, i64>(
+            r#"WITH RECURSIVE ancestors AS (
+                SELECT id, parent FROM category WHERE id = ?
+                UNION ALL
+                SELECT category.id, category.parent FROM category JOIN ancestors ON category.id = ancestors.parent
+            )
+            SELECT COUNT(*) FROM ancestors WHERE id = ?"#
+        )
+            .bind(category)
+            .bind(ancestor)
+            .fetch_one(pool)
+            .await
+            .map(|count| count > 0)
+    }
+
+    async fn create(pool: &MySqlPool, category: &Self) -> Result<(), sqlx::Error> {
+        sqlx::query("INSERT INTO category (id, slug, name, parent, position) VALUE (?, ?, ?, ?, ?)")
+            .bind(&category.id)
+            .bind(&category.slug)
+            .bind(&category.name)
+            .bind(&category.parent)
+            .bind(category.position)
+            .execute(pool)
+            .await
+            .map(|_| ())
+    }
+
+    async fn update(pool: &MySqlPool, category: &Self) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE category SET slug = ?, name = ?, parent = ?, position = ? WHERE id = ?")
+            .bind(&category.slug)
+            .bind(&category.name)
+            .bind(&category.parent)
+            .bind(category.position)
+            .bind(&category.id)
+            .execute(pool)
+            .await
+            .map(|_| ())
+    }
+
+    /// Delete the category, its sub-categories are moved to its parent
+    async fn delete(pool: &MySqlPool, category: &Self) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        sqlx::query("UPDATE category SET parent = ? WHERE parent = ?")
+            .bind(&category.parent)
+            .bind(&category.id)
+            .execute(&mut *tx)
+            .await?;
+
+        // the assignments of the products are deleted by the cascade
+        sqlx::query("DELETE FROM category WHERE id = ?")
+            .bind(&category.id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await
+    }
+
+    /// Assign the product to the category, nothing happens if it already is
+    async fn add_product(pool: &MySqlPool, category: &str, product: &str) -> Result<(), sqlx::Error> {
+        sqlx::query("INSERT IGNORE INTO product_category (product, category) VALUE (?, ?)")
+            .bind(product)
+            .bind(category)
+            .execute(pool)
+            .await
+            .map(|_| ())
+    }
+
+    async fn remove_product(pool: &MySqlPool, category: &str, product: &str) -> Result<bool, sqlx::Error> {
+        sqlx::query("DELETE FROM product_category WHERE product = ? AND category = ?")
+            .bind(product)
+            .bind(category)
+            .execute(pool)
+            .await
+            .map(|res| res.rows_affected() > 0)
+    }
+}
+
+/// Contain every routes for axum
+pub(crate) mod routes {
+    use std::ops::Deref;
+    use axum::extract::{Path, Query, State};
+    use axum::{Form, Json};
+    use axum::http::StatusCode;
+    use axum::response::{IntoResponse, Response};
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+    use sqlx::MySqlPool;
+    use tracing::error;
+    use uuid::Uuid;
+    use crate::AppState;
+    use crate::category::Category;
+    use crate::clients::role::Admin;
+    use crate::declaration::{ApiError, unprocessable};
+    use crate::product::{Product, ProductQuery};
+
+    fn category_not_found() -> Response {
+        (StatusCode::NOT_FOUND, Json(ApiError::new(7003, "This category does not exist"))).into_response()
+    }
+
+    fn slug_conflict() -> Response {
+        (StatusCode::CONFLICT, Json(ApiError::new(7006, "A category already uses this slug"))).into_response()
+    }
+
+    /// Fetch the category of the route, or build the response to return
+    async fn fetch_category(pool: &MySqlPool, slug: &str, error_code: u16, message: &str) -> Result<Category, Response> {
+        match Category::find_by_slug(pool, slug).await {
+            Ok(Some(category)) => Ok(category),
+            Ok(None) => Err(category_not_found()),
+            Err(e) => {
+                error!(target: "FetchCategory", "Cannot fetch the category '{slug}': {e:#?}");
+                Err(Json(ApiError::new(error_code, message)).into_response())
+            }
+        }
+    }
+
+    /// Route: GET /category
+    ///
+    /// Get every category, as a tree
+    pub(crate) async fn get_categories(State(app_state): State<AppState>) -> impl IntoResponse {
+        let pool = app_state.database.get_pool().await;
+
+        match Category::get_all(pool.deref()).await {
+            Ok(categories) => Json(Category::tree(categories)).into_response(),
+            Err(e) => {
+                error!(target: "GetCategories", "Cannot fetch the categories: {e:#?}");
+                Json(ApiError::new(7001, "Cannot get the categories")).into_response()
+            }
+        }
+    }
+
+    /// Route: GET /category/:slug/products
+    ///
+    /// Get a page of the products of the category, including the ones of its sub-categories
+    ///
+    /// Accept the same filters and sorts as `GET /product/page`
+    pub(crate) async fn get_category_products(
+        State(app_state): State<AppState>,
+        Path(slug): Path<String>,
+        Query(mut query): Query<ProductQuery>
+    ) -> impl IntoResponse
+    {
+        if let Err(e) = query.validate() {
+            return unprocessable(e)
+        }
+        let pagination = match query.pagination() {
+            Ok(pagination) => pagination,
+            Err(e) => return unprocessable(e)
+        };
+
+        let pool = app_state.database.get_pool().await;
+
+        let category = match fetch_category(pool.deref(), slug.as_str(), 7002, "Cannot get the products of the category").await {
+            Ok(category) => category,
+            Err(res) => return res
+        };
+
+        query.category = Some(category.slug);
+
+        match Product::get_page(pool.deref(), &query, pagination).await {
+            Ok(products) => Json(products).into_response(),
+            Err(e) => {
+                error!(target: "GetCategoryProducts", "Cannot fetch the products of the category '{slug}': {e:#?}");
+                Json(ApiError::new(7002, "Cannot get the products of the category")).into_response()
+            }
+        }
+    }
+
+    /// Resolve the slug of the parent category given in a form, an empty slug means no parent
+    async fn resolve_parent(pool: &MySqlPool, parent: Option<&str>, error_code: u16, message: &str) -> Result<Option<String>, Response> {
+        match parent.map(str::trim).filter(|p| !p.is_empty()) {
+            Some(slug) => match Category::find_by_slug(pool, slug).await {
+                Ok(Some(parent)) => Ok(Some(parent.id)),
+                Ok(None) => Err(unprocessable(ApiError::new(7007, "The parent category does not exist"))),
+                Err(e) => {
+                    error!(target: "ResolveParentCategory", "Cannot fetch the category '{slug}': {e:#?}");
+                    Err(Json(ApiError::new(error_code, message)).into_response())
+                }
+            },
+            None => Ok(None)
+        }
+    }
+
+    #[derive(Serialize, Deserialize, Clone)]
+    pub(crate) struct CategoryForm {
+        slug: String,
+        name: String,
+        /// Slug of the parent category
+        parent: Option<String>,
+        #[serde(default)]
+        position: u32
+    }
+
+    /// Route: POST /category
+    ///
+    /// Create a new category, restricted to the admins
+    pub(crate) async fn create_category(
+        State(app_state): State<AppState>,
+        _: Admin,
+        Form(form): Form<CategoryForm>
+    ) -> impl IntoResponse
+    {
+        let mut category = Category {
+            id: Uuid::new_v4().to_string(),
+            slug: form.slug.trim().to_string(),
+            name: form.name.trim().to_string(),
+            parent: None,
+            position: form.position
+        };
+        if let Err(e) = category.validate() {
+            return unprocessable(e)
+        }
+
+        let pool = app_state.database.get_pool().await;
+
+        category.parent = match resolve_parent(pool.deref(), form.parent.as_deref(), 7008, "Cannot create the category").await {
+            Ok(parent) => parent,
+            Err(res) => return res
+        };
+
+        match Category::find_by_slug(pool.deref(), category.slug.as_str()).await {
+            Ok(None) => {},
+            Ok(Some(_)) => return slug_conflict(),
+            Err(e) => {
+                error!(target: "CreateCategory", "Cannot fetch the category '{}': {e:#?}", category.slug);
+                return Json(ApiError::new(7008, "Cannot create the category")).into_response()
+            }
+        }
+
+        match Category::create(pool.deref(), &category).await {
+            Ok(_) => (StatusCode::CREATED, Json(category)).into_response(),
+            Err(e) => {
+                error!(target: "CreateCategory", "Cannot create the category: {e:#?}");
+                Json(ApiError::new(7008, "Cannot create the category")).into_response()
+            }
+        }
+    }
+
+    /// Every field is optional, an empty `parent` moves the category to the root of the tree
+    #[derive(Serialize, Deserialize, Clone)]
+    pub(crate) struct CategoryPatchForm {
+        slug: Option<String>,
+        name: Option<String>,
+        parent: Option<String>,
+        position: Option<u32>
+    }
+
+    /// Route: PATCH /category/:slug
+    ///
+    /// Rename or move a category, restricted to the admins
+    pub(crate) async fn edit_category(
+        State(app_state): State<AppState>,
+        Path(slug): Path<String>,
+        _: Admin,
+        Form(form): Form<CategoryPatchForm>
+    ) -> impl IntoResponse
+    {
+        let pool = app_state.database.get_pool().await;
+
+        let old = match fetch_category(pool.deref(), slug.as_str(), 7009, "Cannot edit the category").await {
+            Ok(category) => category,
+            Err(res) => return res
+        };
+
+        let mut category = Category {
+            id: old.id.clone(),
+            slug: form.slug.map(|s| s.trim().to_string()).unwrap_or_else(|| old.slug.clone()),
+            name: form.name.map(|n| n.trim().to_string()).unwrap_or_else(|| old.name.clone()),
+            parent: old.parent.clone(),
+            position: form.position.unwrap_or(old.position)
+        };
+        if let Err(e) = category.validate() {
+            return unprocessable(e)
+        }
+
+        if form.parent.is_some() {
+            category.parent = match resolve_parent(pool.deref(), form.parent.as_deref(), 7009, "Cannot edit the category").await {
+                Ok(parent) => parent,
+                Err(res) => return res
+            };
+        }
+
+        // a category cannot be moved under itself or one of its sub-categories
+        if let Some(parent) = &category.parent {
+            match Category::is_descendant_of(pool.deref(), parent.as_str(), category.id.as_str()).await {
+                Ok(false) => {},
+                Ok(true) => return unprocessable(ApiError::new(7010, "A category cannot be moved under one of its sub-categories")),
+                Err(e) => {
+                    error!(target: "EditCategory", "Cannot check the parents of the category: {e:#?}");
+                    return Json(ApiError::new(7009, "Cannot edit the category")).into_response()
+                }
+            }
+        }
+
+        if category.slug != old.slug {
+            match Category::find_by_slug(pool.deref(), category.slug.as_str()).await {
+                Ok(None) => {},
+                Ok(Some(_)) => return slug_conflict(),
+                Err(e) => {
+                    error!(target: "EditCategory", "Cannot fetch the category '{}': {e:#?}", category.slug);
+                    return Json(ApiError::new(7009, "Cannot edit the category")).into_response()
+                }
+            }
+        }
+
+        match Category::update(pool.deref(), &category).await {
+            Ok(_) => Json(category).into_response(),
+            Err(e) => {
+                error!(target: "EditCategory", "Cannot edit the category '{slug}': {e:#?}");
+                Json(ApiError::new(7009, "Cannot edit the category")).into_response()
+            }
+        }
+    }
+
+    /// Route: DELETE /category/:slug
+    ///
+    /// Delete a category, restricted to the admins
+    ///
+    /// Its sub-categories are moved to its parent, its products are only unassigned
+    pub(crate) async fn delete_category(
+        State(app_state): State<AppState>,
+        Path(slug): Path<String>,
+        _: Admin
+    ) -> impl IntoResponse
+    {
+        let pool = app_state.database.get_pool().await;
+
+        let category = match fetch_category(pool.deref(), slug.as_str(), 7011, "Cannot delete the category").await {
+            Ok(category) => category,
+            Err(res) => return res
+        };
+
+        match Category::delete(pool.deref(), &category).await {
+            Ok(_) => Json(json!({"message": "Category deleted", "code": 7100})).into_response(),
+            Err(e) => {
+                error!(target: "DeleteCategory", "Cannot delete the category '{slug}': {e:#?}");
+                Json(ApiError::new(7011, "Cannot delete the category")).into_response()
+            }
+        }
+    }
+
+    #[derive(Serialize, Deserialize, Clone)]
+    pub(crate) struct CategoryProductForm {
+        product: String
+    }
+
+    /// Route: POST /category/:slug/products
+    ///
+    /// Assign a product to a category, restricted to the admins
+    pub(crate) async fn add_category_product(
+        State(app_state): State<AppState>,
+        Path(slug): Path<String>,
+        _: Admin,
+        Form(form): Form<CategoryProductForm>
+    ) -> impl IntoResponse
+    {
+        let pool = app_state.database.get_pool().await;
+
+        let category = match fetch_category(pool.deref(), slug.as_str(), 7012, "Cannot assign the product").await {
+            Ok(category) => category,
+            Err(res) => return res
+        };
+
+        match Product::find_product(pool.deref(), form.product.as_str()).await {
+            Ok(Some(_)) => {},
+            Ok(None) => return unprocessable(ApiError::new(7013, "This product does not exist")),
+            Err(e) => {
+                error!(target: "AddCategoryProduct", "Cannot fetch the product '{}': {e:#?}", form.product);
+                return Json(ApiError::new(7012, "Cannot assign the product")).into_response()
+            }
+        }
+
+        match Category::add_product(pool.deref(), category.id.as_str(), form.product.as_str()).await {
+            Ok(_) => Json(json!({"message": "Product assigned to the category", "code": 7101})).into_response(),
+            Err(e) => {
+                error!(target: "AddCategoryProduct", "Cannot assign the product to the category '{slug}': {e:#?}");
+                Json(ApiError::new(7012, "Cannot assign the product")).into_response()
+            }
+        }
+    }
+
+    /// Route: DELETE /category/:slug/products/:product
+    ///
+    /// Unassign a product from a category, restricted to the admins
+    pub(crate) async fn remove_category_product(
+        State(app_state): State<AppState>,
+        Path((slug, product)): Path<(String, String)>,
+        _: Admin
+    ) -> impl IntoResponse
+    {
+        let pool = app_state.database.get_pool().await;
+
+        let category = match fetch_category(pool.deref(), slug.as_str(), 7014, "Cannot unassign the product").await {
+            Ok(category) => category,
+            Err(res) => return res
+        };
+
+        match Category::remove_product(pool.deref(), category.id.as_str(), product.as_str()).await {
+            Ok(true) => Json(json!({"message": "Product removed from the category", "code": 7102})).into_response(),
+            Ok(false) => {
+                (StatusCode::NOT_FOUND, Json(ApiError::new(7015, "This product is not in the category"))).into_response()
+            }
+            Err(e) => {
+                error!(target: "RemoveCategoryProduct", "Cannot unassign the product from the category '{slug}': {e:#?}");
+                Json(ApiError::new(7014, "Cannot unassign the product")).into_response()
+            }
+        }
+    }
+}
@@ -0,0 +1,111 @@
+use std::ops::Deref;
+use axum::async_trait;
+use axum::extract::FromRequestParts;
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::http::request::Parts;
+use axum::Json;
+use axum::response::{IntoResponse, Response};
+use tracing::error;
+use crate::AppState;
+use crate::clients::Client;
+use crate::clients::session::Session;
+use crate::constants::{SESSION_COOKIE, SESSION_DURATION_SECONDS};
+use crate::declaration::ApiError;
+
+/// Read the token of the request, from the `Authorization: Bearer` header first, then from the session cookie
+pub(crate) fn read_token(headers: &HeaderMap) -> Option<String> {
+    let bearer = headers.get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(|token| token.trim().to_string());
+
+    if bearer.is_some() {
+        return bearer
+    }
+
+    headers.get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .filter_map(|cookie| cookie.trim().split_once('='))
+        .find(|(name, _)| *name == SESSION_COOKIE)
+        .map(|(_, token)| token.to_string())
+}
+
+/// Build the `Set-Cookie` value storing the session token in the browser
+pub(crate) fn session_cookie(token: &str) -> String {
+    format!("{SESSION_COOKIE}={token}; Path=/; Max-Age={SESSION_DURATION_SECONDS}; HttpOnly; SameSite=Lax")
+}
+
+/// Build the `Set-Cookie` value removing the session token from the browser
+pub(crate) fn expired_session_cookie() -> String {
+    format!("{SESSION_COOKIE}=; Path=/; Max-Age=0; HttpOnly; SameSite=Lax")
+}
+
+fn unauthorized() -> Response {
+    (
+        StatusCode::UNAUTHORIZED,
+        Json(ApiError::new(6007, "Authentication required"))
+    ).into_response()
+}
+
+/// Resolve the session of the request, reject it with a 401 if there is none or if it has expired
+///
+/// Every successful resolution pushes back the expiration of the session
+#[async_trait]
+impl FromRequestParts<AppState> for Session {
+    type Rejection = Response;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        // the session may have already been resolved by another extractor of the same request
+        if let Some(session) = parts.extensions.get::<Session>() {
+            return Ok(session.clone())
+        }
+
+        let Some(token) = read_token(&parts.headers) else {
+            return Err(unauthorized())
+        };
+
+        let pool = state.database.get_pool().await;
+
+        match Session::fetch_active(pool.deref(), token.as_str()).await {
+            Ok(Some(session)) => {
+                parts.extensions.insert(session.clone());
+                Ok(session)
+            }
+            Ok(None) => Err(unauthorized()),
+            Err(e) => {
+                error!(target: "Authentication", "Cannot fetch the session: {e:#?}");
+                Err(Json(ApiError::new(6008, "Cannot authenticate the client")).into_response())
+            }
+        }
+    }
+}
+
+/// Resolve the client owning the session of the request, reject it with a 401 if it is not authenticated
+#[async_trait]
+impl FromRequestParts<AppState> for Client {
+    type Rejection = Response;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        if let Some(client) = parts.extensions.get::<Client>() {
+            return Ok(client.clone())
+        }
+
+        let session = Session::from_request_parts(parts, state).await?;
+
+        let pool = state.database.get_pool().await;
+
+        match Client::fetch_by_id(pool.deref(), session.client.as_str()).await {
+            Ok(Some(client)) => {
+                parts.extensions.insert(client.clone());
+                Ok(client)
+            }
+            Ok(None) => Err(unauthorized()),
+            Err(e) => {
+                error!(target: "Authentication", "Cannot fetch the client: {e:#?}");
+                Err(Json(ApiError::new(6008, "Cannot authenticate the client")).into_response())
+            }
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use std::io::{Cursor, Write};
+use chrono::{NaiveDateTime, Utc};
+use serde::Serialize;
+use sqlx::MySqlPool;
+use zip::write::FileOptions;
+use zip::ZipWriter;
+use crate::clients::Client;
+use crate::clients::session::Session;
+use crate::product::Review;
+use crate::shopping_cart::ShoppingCart;
+
+/// Every personal data tied to a client, as answered to a data access request
+///
+/// Any new table referencing a client must be added here
+#[derive(Serialize)]
+pub(crate) struct PersonalData {
+    pub generated_at: NaiveDateTime,
+    pub profile: Client,
+    pub sessions: Vec<Session>,
+    pub shopping_cart: Option<ShoppingCart>,
+    pub reviews: Vec<Review>
+}
+
+impl PersonalData {
+    pub(crate) async fn collect(pool: &MySqlPool, client: Client) -> Result<Self, sqlx::Error> {
+        let sessions = Session::fetch_all_of_client(pool, client.id.as_str()).await?;
+        let shopping_cart = ShoppingCart::fetch_user_cart(pool, client.id.as_str()).await?;
+        let reviews = Review::get_reviews_of_user(pool, client.id.as_str()).await?;
+
+        Ok(Self {
+            generated_at: Utc::now().naive_utc(),
+            profile: client,
+            sessions,
+            shopping_cart,
+            reviews
+        })
+    }
+
+    pub(crate) fn to_json(&self) -> serde_json::Result<Vec<u8>> {
+        serde_json::to_vec_pretty(self)
+    }
+
+    /// Build a ZIP archive containing the full export and one file per category of data
+    pub(crate) fn to_zip(&self) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        let options = FileOptions::default();
+
+        let files = [
+            ("export.json", serde_json::to_vec_pretty(self)?),
+            ("profile.json", serde_json::to_vec_pretty(&self.profile)?),
+            ("sessions.json", serde_json::to_vec_pretty(&self.sessions)?),
+            ("shopping_cart.json", serde_json::to_vec_pretty(&self.shopping_cart)?),
+            ("reviews.json", serde_json::to_vec_pretty(&self.reviews)?)
+        ];
+
+        for (name, content) in files {
+            zip.start_file(name, options)?;
+            zip.write_all(content.as_slice())?;
+        }
+
+        Ok(zip.finish()?.into_inner())
+    }
+}
@@ -1 +1,769 @@
-pub(crate) type ClientId = String;
\ No newline at end of file
+use serde::{Deserialize, Serialize};
+use sqlx::MySqlPool;
+use tracing::error;
+use uuid::Uuid;
+use crate::clients::password::Verification;
+use crate::clients::role::Role;
+
+pub(crate) mod auth;
+pub(crate) mod export;
+pub(crate) mod password;
+pub(crate) mod password_reset;
+pub(crate) mod role;
+pub(crate) mod session;
+
+pub(crate) type ClientId = String;
+
+#[derive(sqlx::FromRow, Serialize, Deserialize, Clone)]
+pub(crate) struct Client {
+    pub id: ClientId,
+    #[serde(skip_serializing)]
+    pub passwd: String,
+    pub username: Option<String>,
+    pub email: Option<String>,
+    pub role: Role
+}
+
+impl Client {
+    /// Insert a new client, `passwd` must already be hashed with [`password::hash`]
+    async fn create(pool: &MySqlPool, username: &str, email: Option<&str>, passwd: String) -> Result<Self, sqlx::Error> {
+        let client = Self {
+            id: Uuid::new_v4().to_string(),
+            passwd,
+            username: Some(username.to_string()),
+            email: email.map(|e| e.to_string()),
+            role: Role::Customer
+        };
+
+        sqlx::query("INSERT INTO clients (id, passwd, username, email, role) VALUE (?, ?, ?, ?, ?)")
+            .bind(&client.id)
+            .bind(&client.passwd)
+            .bind(&client.username)
+            .bind(&client.email)
+            .bind(client.role)
+            .execute(pool)
+            .await
+            .map(|_| client)
+    }
+
+    async fn fetch_by_email(pool: &MySqlPool, email: &str) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>("SELECT * FROM clients WHERE email = ?")
+            .bind(email)
+            .fetch_optional(pool)
+            .await
+    }
+
+    pub(crate) async fn fetch_by_id(pool: &MySqlPool, id: &str) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>("SELECT * FROM clients WHERE id = ?")
+            .bind(id)
+            .fetch_optional(pool)
+            .await
+    }
+
+    pub(crate) async fn fetch_all(pool: &MySqlPool) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>("SELECT * FROM clients ORDER BY username")
+            .fetch_all(pool)
+            .await
+    }
+
+    /// Fetch the client matching these credentials, `None` if the username or the password is wrong
+    ///
+    /// A password still stored as a legacy SHA-256 digest is rehashed with Argon2id on the fly
+    async fn fetch_by_credentials(pool: &MySqlPool, username: &str, password: &str) -> Result<Option<Self>, sqlx::Error> {
+        let client = sqlx::query_as::<_, Self>("SELECT * FROM clients WHERE username = ?")
+            .bind(username)
+            .fetch_optional(pool)
+            .await?;
+
+        let Some(mut client) = client else {
+            password::verify_dummy(password).await;
+            return Ok(None)
+        };
+
+        match password::verify(password, client.passwd.as_str()).await {
+            Verification::Valid => Ok(Some(client)),
+            Verification::Invalid => Ok(None),
+            Verification::ValidLegacy => {
+                match password::hash(password).await {
+                    Ok(passwd) => {
+                        Self::set_password_hash(pool, client.id.as_str(), passwd.as_str()).await?;
+                        client.passwd = passwd;
+                    }
+                    Err(e) => {
+                        // the client can still log in, the migration will be retried on the next login
+                        error!(target: "Login", "Cannot rehash the legacy password of '{}': {e}", client.id);
+                    }
+                }
+                Ok(Some(client))
+            }
+        }
+    }
+
+    /// Change the role of a client, return `false` if there is no client with this id
+    async fn set_role(pool: &MySqlPool, id: &str, role: Role) -> Result<bool, sqlx::Error> {
+        sqlx::query("UPDATE clients SET role = ? WHERE id = ?")
+            .bind(role)
+            .bind(id)
+            .execute(pool)
+            .await
+            .map(|res| res.rows_affected() > 0)
+    }
+
+    async fn set_username(pool: &MySqlPool, id: &str, username: &str) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE clients SET username = ? WHERE id = ?")
+            .bind(username)
+            .bind(id)
+            .execute(pool)
+            .await
+            .map(|_| ())
+    }
+
+    /// Delete the client, its reviews are kept but anonymised
+    ///
+    /// The sessions and the reset tokens are deleted by the cascade, the shopping cart is deleted explicitly
+    async fn delete(pool: &MySqlPool, id: &str) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        sqlx::query("UPDATE reviews SET user = NULL WHERE user = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query("DELETE FROM shopping_cart WHERE user = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query("DELETE FROM clients WHERE id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await
+    }
+
+    /// Replace the password of the client, `passwd` must already be hashed with [`password::hash`]
+    async fn set_password_hash(pool: &MySqlPool, id: &str, passwd: &str) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE clients SET passwd = ? WHERE id = ?")
+            .bind(passwd)
+            .bind(id)
+            .execute(pool)
+            .await
+            .map(|_| ())
+    }
+}
+
+/// Contain every routes for axum
+pub(crate) mod routes {
+    use std::ops::Deref;
+    use axum::extract::{Path, Query, State};
+    use axum::{Form, Json};
+    use axum::http::{header, HeaderMap, StatusCode};
+    use axum::response::{AppendHeaders, IntoResponse, Response};
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+    use sqlx::MySqlPool;
+    use tracing::error;
+    use crate::AppState;
+    use crate::clients::{Client, password};
+    use crate::clients::export::PersonalData;
+    use crate::clients::password::Verification;
+    use crate::clients::auth::{expired_session_cookie, session_cookie};
+    use crate::clients::role::{Admin, Role, Staff};
+    use crate::clients::session::Session;
+    use crate::clients::password_reset::PasswordReset;
+    use crate::constants::{EMAIL_MAX_LENGTH, PASSWORD_MIN_LENGTH, PASSWORD_RESET_DURATION_SECONDS, USERNAME_MAX_LENGTH};
+    use crate::declaration::{ApiError, is_unique_violation};
+    use crate::mailer::Mail;
+    use crate::rate_limit::too_many_requests;
+    use crate::shopping_cart::guest::{expired_guest_cookie, merge_guest_cart};
+
+    #[derive(Serialize, Deserialize, Clone)]
+    pub(crate) struct CredentialsForm {
+        username: String,
+        password: String
+    }
+
+    #[derive(Serialize, Deserialize, Clone)]
+    pub(crate) struct RegisterForm {
+        username: String,
+        password: String,
+        email: Option<String>
+    }
+
+    /// Check the rules every new password must follow
+    fn check_password(password: &str) -> Result<(), ApiError> {
+        if password.chars().count() < PASSWORD_MIN_LENGTH {
+            return Err(ApiError::new(6002, format!("The password must contain at least {PASSWORD_MIN_LENGTH} characters")))
+        }
+        Ok(())
+    }
+
+    fn is_valid_email(email: &str) -> bool {
+        let Some((local, domain)) = email.split_once('@') else { return false };
+
+        email.len() <= EMAIL_MAX_LENGTH
+            && !local.is_empty()
+            && domain.contains('.')
+            && !domain.contains('@')
+            && !email.chars().any(char::is_whitespace)
+    }
+
+    /// Open a new session for the client and build the response giving back its token
+    ///
+    /// The guest cart of the visitor, if any, is merged into the cart of the client
+    async fn open_session(
+        pool: &MySqlPool,
+        cookie_key: &[u8],
+        client: &Client,
+        headers: &HeaderMap,
+        code: u16,
+        message: &str
+    ) -> Response
+    {
+        let user_agent = headers.get(header::USER_AGENT).and_then(|value| value.to_str().ok());
+
+        match Session::create(pool, client.id.as_str(), user_agent).await {
+            Ok((session, token)) => {
+                let mut cookies = vec![(header::SET_COOKIE, session_cookie(token.as_str()))];
+                if merge_guest_cart(pool, cookie_key, headers, client.id.as_str()).await {
+                    cookies.push((header::SET_COOKIE, expired_guest_cookie()));
+                }
+
+                (
+                    AppendHeaders(cookies),
+                    Json(
+                        json!({
+                            "id": client.id,
+                            "token": token,
+                            "expires_at": session.expires_at,
+                            "code": code,
+                            "message": message
+                        })
+                    )
+                ).into_response()
+            }
+            Err(e) => {
+                error!(target: "OpenSession", "Cannot create the session: {e:#?}");
+                Json(ApiError::new(6009, "Cannot open a session")).into_response()
+            }
+        }
+    }
+
+    /// Route: POST /clients/register
+    ///
+    /// Create a new account and open a session for it
+    pub(crate) async fn register(
+        State(app_state): State<AppState>,
+        headers: HeaderMap,
+        Form(form): Form<RegisterForm>
+    ) -> impl IntoResponse
+    {
+        let username = form.username.trim();
+        if username.is_empty() || username.chars().count() > USERNAME_MAX_LENGTH {
+            return Json(
+                ApiError::new(6001, format!("The username must contain between 1 and {USERNAME_MAX_LENGTH} characters"))
+            ).into_response()
+        }
+        if let Err(e) = check_password(form.password.as_str()) {
+            return Json(e).into_response()
+        }
+        let email = form.email.as_deref().map(str::trim).filter(|email| !email.is_empty());
+        if email.is_some_and(|email| !is_valid_email(email)) {
+            return Json(ApiError::new(6018, "Invalid email address")).into_response()
+        }
+
+        let pool = app_state.database.get_pool().await;
+
+        let passwd = match password::hash(form.password.as_str()).await {
+            Ok(passwd) => passwd,
+            Err(e) => {
+                error!(target: "Register", "Cannot hash the password: {e}");
+                return Json(ApiError::new(6004, "Cannot create the account")).into_response()
+            }
+        };
+
+        match Client::create(pool.deref(), username, email, passwd).await {
+            Ok(client) => open_session(pool.deref(), app_state.cookie_key.as_slice(), &client, &headers, 6100, "Account created").await,
+            Err(e) if is_unique_violation(&e) => taken(&e),
+            Err(e) => {
+                error!(target: "Register", "Cannot create the account: {e:#?}");
+                Json(ApiError::new(6004, "Cannot create the account")).into_response()
+            }
+        }
+    }
+
+    /// Build the response refusing a username or an email address already used by another client
+    ///
+    /// The `UNIQUE` constraints of the `clients` table are named after their column, and MySQL ends its message with that name
+    fn taken(e: &sqlx::Error) -> Response {
+        let on_email = e.as_database_error().is_some_and(|e| e.message().trim_end_matches('\'').ends_with("email"));
+
+        let error = match on_email {
+            true => ApiError::new(6019, "This email address is already used"),
+            false => ApiError::new(6003, "This username is already taken")
+        };
+        (StatusCode::CONFLICT, Json(error)).into_response()
+    }
+
+    /// Route: POST /clients/login
+    ///
+    /// Open a new session for the account matching the credentials
+    pub(crate) async fn login(
+        State(app_state): State<AppState>,
+        headers: HeaderMap,
+        Form(form): Form<CredentialsForm>
+    ) -> impl IntoResponse
+    {
+        let username = form.username.trim();
+
+        if let Some(retry_after) = app_state.rate_limiter.login_lockout(username) {
+            return too_many_requests(retry_after, 6029, "Too many failed logins, retry later")
+        }
+
+        let pool = app_state.database.get_pool().await;
+
+        match Client::fetch_by_credentials(pool.deref(), username, form.password.as_str()).await {
+            Ok(Some(client)) => {
+                app_state.rate_limiter.login_succeeded(username);
+                open_session(pool.deref(), app_state.cookie_key.as_slice(), &client, &headers, 6101, "Logged in").await
+            }
+            Ok(None) => {
+                app_state.rate_limiter.login_failed(username);
+                (
+                    StatusCode::UNAUTHORIZED,
+                    Json(ApiError::new(6005, "Invalid username or password"))
+                ).into_response()
+            }
+            Err(e) => {
+                error!(target: "Login", "Cannot fetch the client: {e:#?}");
+                Json(ApiError::new(6006, "Cannot log in")).into_response()
+            }
+        }
+    }
+
+    /// Route: POST /clients/logout
+    ///
+    /// Close the session of the request
+    pub(crate) async fn logout(
+        State(app_state): State<AppState>,
+        session: Session
+    ) -> impl IntoResponse
+    {
+        let pool = app_state.database.get_pool().await;
+
+        match Session::delete(pool.deref(), session.id.as_str()).await {
+            Ok(_) => {
+                (
+                    [(header::SET_COOKIE, expired_session_cookie())],
+                    Json(json!({"message": "Logged out", "code": 6102}))
+                ).into_response()
+            }
+            Err(e) => {
+                error!(target: "Logout", "Cannot delete the session: {e:#?}");
+                Json(ApiError::new(6010, "Cannot log out")).into_response()
+            }
+        }
+    }
+
+    #[derive(Serialize, Deserialize, Clone)]
+    pub(crate) struct ForgotPasswordForm {
+        email: String
+    }
+
+    /// Create a reset token for the client owning the email address and mail the link to it, nothing if the address is unknown
+    async fn send_reset_link(pool: &MySqlPool, app_state: &AppState, email: &str) -> Result<(), String> {
+        let client = Client::fetch_by_email(pool, email).await
+            .map_err(|e| format!("Cannot fetch the client: {e:#?}"))?;
+
+        let Some(client) = client else { return Ok(()) };
+
+        let token = PasswordReset::create(pool, client.id.as_str()).await
+            .map_err(|e| format!("Cannot create the reset token: {e:#?}"))?;
+
+        let mail = Mail {
+            to: email.to_string(),
+            subject: "Pedalize - Réinitialisation de votre mot de passe".to_string(),
+            body: format!(
+                "Bonjour {username},\n\nPour choisir un nouveau mot de passe, ouvrez ce lien dans les {minutes} minutes :\n{site}/reset_password.html?token={token}\n\nSi vous n'êtes pas à l'origine de cette demande, ignorez ce message.",
+                username = client.username.unwrap_or_default(),
+                minutes = PASSWORD_RESET_DURATION_SECONDS / 60,
+                site = app_state.config.site_url.trim_end_matches('/')
+            )
+        };
+
+        app_state.mailer.send(mail).await
+            .map_err(|e| format!("Cannot send the reset email: {e}"))
+    }
+
+    /// Route: POST /clients/password/forgot
+    ///
+    /// Send a password reset link to the email address if it belongs to a client
+    ///
+    /// The response is the same whether the address is known or not, to not disclose the registered addresses
+    pub(crate) async fn forgot_password(
+        State(app_state): State<AppState>,
+        Form(form): Form<ForgotPasswordForm>
+    ) -> impl IntoResponse
+    {
+        let pool = app_state.database.get_pool().await.clone();
+
+        // sent in the background, a slower answer would tell that the address is registered
+        // and a failure is only logged for the same reason
+        tokio::spawn(async move {
+            if let Err(e) = send_reset_link(&pool, &app_state, form.email.trim()).await {
+                error!(target: "ForgotPassword", "{e}");
+            }
+        });
+
+        Json(json!({"message": "If this address is known, a reset link has been sent", "code": 6105})).into_response()
+    }
+
+    #[derive(Serialize, Deserialize, Clone)]
+    pub(crate) struct ResetPasswordForm {
+        token: String,
+        password: String
+    }
+
+    /// Route: POST /clients/password/reset
+    ///
+    /// Choose a new password with a reset token, every session of the client is then revoked
+    pub(crate) async fn reset_password(
+        State(app_state): State<AppState>,
+        Form(form): Form<ResetPasswordForm>
+    ) -> impl IntoResponse
+    {
+        if let Err(e) = check_password(form.password.as_str()) {
+            return Json(e).into_response()
+        }
+
+        let passwd = match password::hash(form.password.as_str()).await {
+            Ok(passwd) => passwd,
+            Err(e) => {
+                error!(target: "ResetPassword", "Cannot hash the password: {e}");
+                return Json(ApiError::new(6022, "Cannot reset the password")).into_response()
+            }
+        };
+
+        let pool = app_state.database.get_pool().await;
+
+        // the token stays usable if the password cannot be changed
+        let client = match PasswordReset::redeem(pool.deref(), form.token.trim(), passwd.as_str()).await {
+            Ok(Some(client)) => client,
+            Ok(None) => {
+                return Json(ApiError::new(6021, "This reset link is invalid or has expired")).into_response()
+            }
+            Err(e) => {
+                error!(target: "ResetPassword", "Cannot change the password with the reset token: {e:#?}");
+                return Json(ApiError::new(6022, "Cannot reset the password")).into_response()
+            }
+        };
+
+        if let Err(e) = Session::delete_all_of_client(pool.deref(), client.as_str()).await {
+            error!(target: "ResetPassword", "Cannot revoke the sessions of '{client}': {e:#?}");
+        }
+
+        Json(json!({"message": "Password changed", "code": 6106})).into_response()
+    }
+
+    /// Route: GET /clients/me
+    ///
+    /// Get the account of the authenticated client
+    pub(crate) async fn get_me(client: Client) -> impl IntoResponse {
+        Json(client)
+    }
+
+    #[derive(Serialize, Deserialize, Clone)]
+    pub(crate) struct EditMeForm {
+        username: String
+    }
+
+    /// Route: PATCH /clients/me
+    ///
+    /// Change the username of the authenticated client
+    pub(crate) async fn edit_me(
+        State(app_state): State<AppState>,
+        client: Client,
+        Form(form): Form<EditMeForm>
+    ) -> impl IntoResponse
+    {
+        let username = form.username.trim();
+        if username.is_empty() || username.chars().count() > USERNAME_MAX_LENGTH {
+            return Json(
+                ApiError::new(6001, format!("The username must contain between 1 and {USERNAME_MAX_LENGTH} characters"))
+            ).into_response()
+        }
+
+        if client.username.as_deref() == Some(username) {
+            return Json(client).into_response()
+        }
+
+        let pool = app_state.database.get_pool().await;
+
+        match Client::set_username(pool.deref(), client.id.as_str(), username).await {
+            Ok(_) => Json(Client { username: Some(username.to_string()), ..client }).into_response(),
+            Err(e) if is_unique_violation(&e) => taken(&e),
+            Err(e) => {
+                error!(target: "EditMe", "Cannot change the username of '{}': {e:#?}", client.id);
+                Json(ApiError::new(6023, "Cannot change the username")).into_response()
+            }
+        }
+    }
+
+    #[derive(Serialize, Deserialize, Clone)]
+    pub(crate) struct ChangePasswordForm {
+        old_password: String,
+        new_password: String
+    }
+
+    /// Route: POST /clients/me/password
+    ///
+    /// Change the password of the authenticated client, every other session is revoked
+    pub(crate) async fn change_password(
+        State(app_state): State<AppState>,
+        session: Session,
+        client: Client,
+        Form(form): Form<ChangePasswordForm>
+    ) -> impl IntoResponse
+    {
+        if password::verify(form.old_password.as_str(), client.passwd.as_str()).await == Verification::Invalid {
+            return (
+                StatusCode::FORBIDDEN,
+                Json(ApiError::new(6024, "The old password is wrong"))
+            ).into_response()
+        }
+        if let Err(e) = check_password(form.new_password.as_str()) {
+            return Json(e).into_response()
+        }
+
+        let passwd = match password::hash(form.new_password.as_str()).await {
+            Ok(passwd) => passwd,
+            Err(e) => {
+                error!(target: "ChangePassword", "Cannot hash the password: {e}");
+                return Json(ApiError::new(6025, "Cannot change the password")).into_response()
+            }
+        };
+
+        let pool = app_state.database.get_pool().await;
+
+        if let Err(e) = Client::set_password_hash(pool.deref(), client.id.as_str(), passwd.as_str()).await {
+            error!(target: "ChangePassword", "Cannot change the password of '{}': {e:#?}", client.id);
+            return Json(ApiError::new(6025, "Cannot change the password")).into_response()
+        }
+
+        if let Err(e) = Session::delete_others_of_client(pool.deref(), client.id.as_str(), session.id.as_str()).await {
+            error!(target: "ChangePassword", "Cannot revoke the sessions of '{}': {e:#?}", client.id);
+        }
+
+        Json(json!({"message": "Password changed", "code": 6106})).into_response()
+    }
+
+    #[derive(Serialize, Deserialize, Clone)]
+    pub(crate) struct DeleteMeForm {
+        password: String
+    }
+
+    /// Route: DELETE /clients/me
+    ///
+    /// Delete the account of the authenticated client, its password is asked again as a confirmation
+    pub(crate) async fn delete_me(
+        State(app_state): State<AppState>,
+        client: Client,
+        Form(form): Form<DeleteMeForm>
+    ) -> impl IntoResponse
+    {
+        if password::verify(form.password.as_str(), client.passwd.as_str()).await == Verification::Invalid {
+            return (
+                StatusCode::FORBIDDEN,
+                Json(ApiError::new(6026, "The password is wrong"))
+            ).into_response()
+        }
+
+        let pool = app_state.database.get_pool().await;
+
+        match Client::delete(pool.deref(), client.id.as_str()).await {
+            Ok(_) => {
+                (
+                    [(header::SET_COOKIE, expired_session_cookie())],
+                    Json(json!({"message": "Account deleted", "code": 6107}))
+                ).into_response()
+            }
+            Err(e) => {
+                error!(target: "DeleteMe", "Cannot delete the account '{}': {e:#?}", client.id);
+                Json(ApiError::new(6027, "Cannot delete the account")).into_response()
+            }
+        }
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Copy, Default)]
+    #[serde(rename_all = "lowercase")]
+    pub(crate) enum ExportFormat {
+        #[default]
+        Json,
+        Zip
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub(crate) struct ExportQuery {
+        #[serde(default)]
+        format: ExportFormat
+    }
+
+    /// Route: GET /clients/me/export
+    ///
+    /// Download every personal data of the authenticated client, as a JSON file or a ZIP archive
+    pub(crate) async fn export_me(
+        State(app_state): State<AppState>,
+        client: Client,
+        Query(query): Query<ExportQuery>
+    ) -> impl IntoResponse
+    {
+        let pool = app_state.database.get_pool().await;
+        let client_id = client.id.clone();
+
+        let data = match PersonalData::collect(pool.deref(), client).await {
+            Ok(data) => data,
+            Err(e) => {
+                error!(target: "ExportMe", "Cannot collect the data of '{client_id}': {e:#?}");
+                return Json(ApiError::new(6028, "Cannot export the personal data")).into_response()
+            }
+        };
+
+        let date = data.generated_at.format("%Y-%m-%d");
+        let file = match query.format {
+            ExportFormat::Json => data.to_json()
+                .map(|content| (content, "application/json", format!("pedalize-export-{date}.json")))
+                .map_err(|e| e.to_string()),
+            ExportFormat::Zip => data.to_zip()
+                .map(|content| (content, "application/zip", format!("pedalize-export-{date}.zip")))
+                .map_err(|e| e.to_string())
+        };
+
+        match file {
+            Ok((content, content_type, filename)) => {
+                (
+                    [
+                        (header::CONTENT_TYPE, content_type.to_string()),
+                        (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{filename}\""))
+                    ],
+                    content
+                ).into_response()
+            }
+            Err(e) => {
+                error!(target: "ExportMe", "Cannot build the export of '{client_id}': {e}");
+                Json(ApiError::new(6028, "Cannot export the personal data")).into_response()
+            }
+        }
+    }
+
+    /// Route: GET /clients/me/sessions
+    ///
+    /// List the active sessions of the authenticated client
+    pub(crate) async fn get_sessions(
+        State(app_state): State<AppState>,
+        session: Session
+    ) -> impl IntoResponse
+    {
+        let pool = app_state.database.get_pool().await;
+
+        match Session::fetch_all_of_client(pool.deref(), session.client.as_str()).await {
+            Ok(sessions) => {
+                let sessions = sessions.into_iter()
+                    .map(|s| {
+                        let current = s.id == session.id;
+                        json!({ "current": current, "session": s })
+                    })
+                    .collect::<Vec<_>>();
+
+                Json(sessions).into_response()
+            }
+            Err(e) => {
+                error!(target: "GetSessions", "Cannot fetch the sessions: {e:#?}");
+                Json(ApiError::new(6011, "Cannot get the sessions")).into_response()
+            }
+        }
+    }
+
+    /// Route: DELETE /clients/me/sessions
+    ///
+    /// Revoke every session of the authenticated client, including the current one
+    pub(crate) async fn revoke_sessions(
+        State(app_state): State<AppState>,
+        session: Session
+    ) -> impl IntoResponse
+    {
+        let pool = app_state.database.get_pool().await;
+
+        match Session::delete_all_of_client(pool.deref(), session.client.as_str()).await {
+            Ok(count) => {
+                (
+                    [(header::SET_COOKIE, expired_session_cookie())],
+                    Json(json!({"revoked": count, "message": "Sessions revoked", "code": 6103}))
+                ).into_response()
+            }
+            Err(e) => {
+                error!(target: "RevokeSessions", "Cannot delete the sessions: {e:#?}");
+                Json(ApiError::new(6012, "Cannot revoke the sessions")).into_response()
+            }
+        }
+    }
+
+    /// Route: GET /admin/clients
+    ///
+    /// List every client with its role, restricted to the staff
+    pub(crate) async fn get_all_clients(
+        State(app_state): State<AppState>,
+        _: Staff
+    ) -> impl IntoResponse
+    {
+        let pool = app_state.database.get_pool().await;
+
+        match Client::fetch_all(pool.deref()).await {
+            Ok(clients) => Json(clients).into_response(),
+            Err(e) => {
+                error!(target: "GetAllClients", "Cannot fetch the clients: {e:#?}");
+                Json(ApiError::new(6014, "Cannot get the clients")).into_response()
+            }
+        }
+    }
+
+    #[derive(Serialize, Deserialize, Clone)]
+    pub(crate) struct RoleForm {
+        role: Role
+    }
+
+    /// Route: PATCH /admin/clients/:id/role
+    ///
+    /// Change the role of a client, restricted to the admins
+    pub(crate) async fn set_client_role(
+        State(app_state): State<AppState>,
+        Path(id): Path<String>,
+        Admin(admin): Admin,
+        Form(form): Form<RoleForm>
+    ) -> impl IntoResponse
+    {
+        // prevent the last admin from locking everyone out of the administration
+        if admin.id == id {
+            return (
+                StatusCode::FORBIDDEN,
+                Json(ApiError::new(6015, "An admin cannot change its own role"))
+            ).into_response()
+        }
+
+        let pool = app_state.database.get_pool().await;
+
+        match Client::set_role(pool.deref(), id.as_str(), form.role).await {
+            Ok(true) => Json(json!({"message": "Role changed", "code": 6104})).into_response(),
+            Ok(false) => {
+                (
+                    StatusCode::NOT_FOUND,
+                    Json(ApiError::new(6016, "This client does not exist"))
+                ).into_response()
+            }
+            Err(e) => {
+                error!(target: "SetClientRole", "Cannot change the role of '{id}': {e:#?}");
+                Json(ApiError::new(6017, "Cannot change the role")).into_response()
+            }
+        }
+    }
+}
\ No newline at end of file
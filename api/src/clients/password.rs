@@ -0,0 +1,66 @@
+use std::sync::OnceLock;
+use argon2::{Argon2, PasswordHash, PasswordHasher, PasswordVerifier};
+use argon2::password_hash::SaltString;
+use argon2::password_hash::rand_core::OsRng;
+use sha2::{Digest, Sha256};
+
+/// Result of the verification of a password against the `clients.passwd` column
+#[derive(PartialEq, Eq)]
+pub(crate) enum Verification {
+    Valid,
+    /// The password is valid, but stored as an old unsalted SHA-256 digest and must be rehashed
+    ValidLegacy,
+    Invalid
+}
+
+/// Hash a password with Argon2id, the result is a PHC string containing the salt and the parameters
+///
+/// Argon2 is CPU bound on purpose, it runs on the blocking thread pool to not stall the runtime
+pub(crate) async fn hash(password: &str) -> Result<String, String> {
+    let password = password.to_string();
+    tokio::task::spawn_blocking(move || hash_blocking(&password))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+fn hash_blocking(password: &str) -> Result<String, argon2::password_hash::Error> {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+}
+
+/// Check a password against a stored hash, either an Argon2 PHC string or a legacy SHA-256 hex digest
+pub(crate) async fn verify(password: &str, stored: &str) -> Verification {
+    let (password, stored) = (password.to_string(), stored.to_string());
+    tokio::task::spawn_blocking(move || verify_blocking(&password, &stored))
+        .await
+        .unwrap_or(Verification::Invalid)
+}
+
+fn verify_blocking(password: &str, stored: &str) -> Verification {
+    if let Ok(hash) = PasswordHash::new(stored) {
+        return match Argon2::default().verify_password(password.as_bytes(), &hash) {
+            Ok(_) => Verification::Valid,
+            Err(_) => Verification::Invalid
+        }
+    }
+
+    let digest = hex::encode(Sha256::digest(password.as_bytes()));
+    let matching = digest.len() == stored.len()
+        && digest.bytes().zip(stored.to_ascii_lowercase().bytes()).fold(0, |acc, (a, b)| acc | (a ^ b)) == 0;
+
+    if matching { Verification::ValidLegacy } else { Verification::Invalid }
+}
+
+/// Run a verification against a throwaway hash, so that an unknown username takes as long to refuse as a wrong password
+pub(crate) async fn verify_dummy(password: &str) {
+    static DUMMY_HASH: OnceLock<String> = OnceLock::new();
+
+    let password = password.to_string();
+    let _ = tokio::task::spawn_blocking(move || {
+        let stored = DUMMY_HASH.get_or_init(|| hash_blocking("dummy password").unwrap_or_default());
+        verify_blocking(&password, stored)
+    }).await;
+}
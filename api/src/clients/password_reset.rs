@@ -0,0 +1,69 @@
+use sqlx::MySqlPool;
+use crate::clients::ClientId;
+use crate::clients::session::{generate_token, hash_token};
+use crate::constants::PASSWORD_RESET_DURATION_SECONDS;
+
+/// Single-use tokens allowing a client to choose a new password, only their SHA-256 digest is stored
+pub(crate) struct PasswordReset;
+
+impl PasswordReset {
+    /// Create a new reset token for the client, replacing the previous ones
+    pub(crate) async fn create(pool: &MySqlPool, client: &str) -> Result<String, sqlx::Error> {
+        let token = generate_token();
+
+        let mut tx = pool.begin().await?;
+
+        sqlx::query("DELETE FROM password_resets WHERE client = ? OR expires_at <= NOW()")
+            .bind(client)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query(
+            r#"INSERT INTO password_resets (token_hash, client, created_at, expires_at)
+            VALUE (?, ?, NOW(), DATE_ADD(NOW(), INTERVAL ? SECOND))"#
+        )
+            .bind(hash_token(token.as_str()))
+            .bind(client)
+            .bind(PASSWORD_RESET_DURATION_SECONDS)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(token)
+    }
+
+    /// Mark the token as used and set the new password hash of its client in the same transaction
+    ///
+    /// Return the client, `None` if the token is unknown, expired or already used
+    pub(crate) async fn redeem(pool: &MySqlPool, token: &str, passwd: &str) -> Result<Option<ClientId>, sqlx::Error> {
+        let token_hash = hash_token(token);
+
+        let mut tx = pool.begin().await?;
+
+        let res = sqlx::query(
+            r#"UPDATE password_resets SET used_at = NOW()
+            WHERE token_hash = ? AND used_at IS NULL AND expires_at > NOW()"#
+        )
+            .bind(&token_hash)
+            .execute(&mut *tx)
+            .await?;
+
+        if res.rows_affected() == 0 {
+            return Ok(None)
+        }
+
+        let client: ClientId = sqlx::query_scalar("SELECT client FROM password_resets WHERE token_hash = ?")
+            .bind(&token_hash)
+            .fetch_one(&mut *tx)
+            .await?;
+
+        sqlx::query("UPDATE clients SET passwd = ? WHERE id = ?")
+            .bind(passwd)
+            .bind(client.as_str())
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(Some(client))
+    }
+}
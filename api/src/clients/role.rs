@@ -0,0 +1,75 @@
+use axum::async_trait;
+use axum::extract::FromRequestParts;
+use axum::http::{Request, StatusCode};
+use axum::http::request::Parts;
+use axum::Json;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use serde::{Deserialize, Serialize};
+use crate::AppState;
+use crate::clients::Client;
+use crate::database::string_enum_type;
+use crate::declaration::ApiError;
+
+/// Role of a client, stored in the `clients.role` column
+///
+/// The roles are ordered, each one has the rights of the previous ones
+#[derive(sqlx::Encode, sqlx::Decode, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[sqlx(rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Role {
+    Customer,
+    Staff,
+    Admin
+}
+
+string_enum_type!(Role);
+
+fn forbidden() -> Response {
+    (
+        StatusCode::FORBIDDEN,
+        Json(ApiError::new(6013, "Insufficient permissions"))
+    ).into_response()
+}
+
+async fn client_with_role(parts: &mut Parts, state: &AppState, role: Role) -> Result<Client, Response> {
+    let client = Client::from_request_parts(parts, state).await?;
+
+    if client.role >= role {
+        Ok(client)
+    } else {
+        Err(forbidden())
+    }
+}
+
+/// An authenticated client with at least the staff role, reject the request with a 403 otherwise
+pub(crate) struct Staff(pub Client);
+
+#[async_trait]
+impl FromRequestParts<AppState> for Staff {
+    type Rejection = Response;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        client_with_role(parts, state, Role::Staff).await.map(Self)
+    }
+}
+
+/// An authenticated client with the admin role, reject the request with a 403 otherwise
+pub(crate) struct Admin(pub Client);
+
+#[async_trait]
+impl FromRequestParts<AppState> for Admin {
+    type Rejection = Response;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        client_with_role(parts, state, Role::Admin).await.map(Self)
+    }
+}
+
+/// Middleware restricting the routes to the staff, the admin only routes must also extract [`Admin`]
+///
+/// The resolved client is kept in the request extensions, so the handlers do not fetch it again
+pub(crate) async fn staff_layer<B>(Staff(client): Staff, mut req: Request<B>, next: Next<B>) -> Response {
+    req.extensions_mut().insert(client);
+    next.run(req).await
+}
@@ -0,0 +1,127 @@
+use chrono::NaiveDateTime;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::MySqlPool;
+use uuid::Uuid;
+use crate::clients::ClientId;
+use crate::constants::{SESSION_DURATION_SECONDS, USER_AGENT_MAX_LENGTH};
+
+pub(crate) type SessionId = String;
+
+/// A login of a client, the token itself is never stored, only its SHA-256 digest
+#[derive(sqlx::FromRow, Serialize, Deserialize, Clone)]
+pub(crate) struct Session {
+    pub id: SessionId,
+    pub client: ClientId,
+    pub created_at: NaiveDateTime,
+    pub expires_at: NaiveDateTime,
+    pub last_seen: NaiveDateTime,
+    pub user_agent: Option<String>
+}
+
+/// Generate a new random token of 64 hexadecimal characters
+pub(crate) fn generate_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+/// Digest of a token as stored in the database, the tokens themselves are never stored
+pub(crate) fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+impl Session {
+    /// Open a new session for the client, return the session and the token to give back to the client
+    pub(crate) async fn create(pool: &MySqlPool, client: &str, user_agent: Option<&str>) -> Result<(Self, String), sqlx::Error> {
+        Self::delete_expired(pool).await?;
+
+        let token = generate_token();
+        let id = Uuid::new_v4().to_string();
+        let user_agent = user_agent.map(|ua| ua.chars().take(USER_AGENT_MAX_LENGTH).collect::<String>());
+
+        sqlx::query(
+            r#"INSERT INTO sessions (id, token_hash, client, created_at, expires_at, last_seen, user_agent)
+            VALUE (?, ?, ?, NOW(), DATE_ADD(NOW(), INTERVAL ? SECOND), NOW(), ?)"#
+        )
+            .bind(&id)
+            .bind(hash_token(token.as_str()))
+            .bind(client)
+            .bind(SESSION_DURATION_SECONDS)
+            .bind(user_agent)
+            .execute(pool)
+            .await?;
+
+        let session = sqlx::query_as::<_, Self>("SELECT id, client, created_at, expires_at, last_seen, user_agent FROM sessions WHERE id = ?")
+            .bind(&id)
+            .fetch_one(pool)
+            .await?;
+
+        Ok((session, token))
+    }
+
+    /// Fetch the session matching the token if it has not expired, and push back its expiration
+    pub(crate) async fn fetch_active(pool: &MySqlPool, token: &str) -> Result<Option<Self>, sqlx::Error> {
+        let token_hash = hash_token(token);
+
+        let res = sqlx::query(
+            r#"UPDATE sessions
+            SET last_seen = NOW(), expires_at = DATE_ADD(NOW(), INTERVAL ? SECOND)
+            WHERE token_hash = ? AND expires_at > NOW()"#
+        )
+            .bind(SESSION_DURATION_SECONDS)
+            .bind(&token_hash)
+            .execute(pool)
+            .await?;
+
+        if res.rows_affected() == 0 {
+            return Ok(None)
+        }
+
+        sqlx::query_as::<_, Self>("SELECT id, client, created_at, expires_at, last_seen, user_agent FROM sessions WHERE token_hash = ?")
+            .bind(&token_hash)
+            .fetch_optional(pool)
+            .await
+    }
+
+    pub(crate) async fn fetch_all_of_client(pool: &MySqlPool, client: &str) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>("SELECT id, client, created_at, expires_at, last_seen, user_agent FROM sessions WHERE client = ? AND expires_at > NOW() ORDER BY last_seen DESC")
+            .bind(client)
+            .fetch_all(pool)
+            .await
+    }
+
+    pub(crate) async fn delete(pool: &MySqlPool, id: &str) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM sessions WHERE id = ?")
+            .bind(id)
+            .execute(pool)
+            .await
+            .map(|_| ())
+    }
+
+    pub(crate) async fn delete_all_of_client(pool: &MySqlPool, client: &str) -> Result<u64, sqlx::Error> {
+        sqlx::query("DELETE FROM sessions WHERE client = ?")
+            .bind(client)
+            .execute(pool)
+            .await
+            .map(|res| res.rows_affected())
+    }
+
+    /// Delete every session of the client but the one kept
+    pub(crate) async fn delete_others_of_client(pool: &MySqlPool, client: &str, kept: &str) -> Result<u64, sqlx::Error> {
+        sqlx::query("DELETE FROM sessions WHERE client = ? AND id != ?")
+            .bind(client)
+            .bind(kept)
+            .execute(pool)
+            .await
+            .map(|res| res.rows_affected())
+    }
+
+    async fn delete_expired(pool: &MySqlPool) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM sessions WHERE expires_at <= NOW()")
+            .execute(pool)
+            .await
+            .map(|_| ())
+    }
+}
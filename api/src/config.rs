@@ -0,0 +1,86 @@
+use std::error::Error;
+use std::fs;
+use axum::http::HeaderValue;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+use crate::mailer::MailerConfig;
+use crate::product::moderation::ModerationConfig;
+use crate::rate_limit::RateLimitConfig;
+
+/// Optional settings of the API, read from `api_config.json`
+///
+/// Every field has a default value, so the file can be missing or partial
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub(crate) struct Config {
+    /// Hex encoded key used to sign the cookies, a random one is used if missing
+    pub cookie_secret: Option<String>,
+    /// Public address of the website, used to build the links sent by email
+    pub site_url: String,
+    /// Origins of the pages allowed to call the API with their cookies, scheme, host and port, e.g. `http://127.0.0.1:5500`
+    pub allowed_origins: Vec<String>,
+    /// Directory the uploaded images are stored in
+    pub image_dir: String,
+    pub mailer: MailerConfig,
+    pub rate_limit: RateLimitConfig,
+    pub moderation: ModerationConfig
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            cookie_secret: None,
+            site_url: "http://localhost".to_string(),
+            allowed_origins: vec!["http://localhost".to_string(), "http://127.0.0.1".to_string()],
+            image_dir: "images".to_string(),
+            mailer: MailerConfig::default(),
+            rate_limit: RateLimitConfig::default(),
+            moderation: ModerationConfig::default()
+        }
+    }
+}
+
+impl Config {
+    fn read() -> Result<Self, Box<dyn Error>> {
+        let c = fs::read_to_string("api_config.json")?;
+        serde_json::from_str(c.as_str()).map_err(|e| e.into())
+    }
+
+    pub(crate) fn init() -> Self {
+        match Self::read() {
+            Ok(c) => c,
+            Err(e) => {
+                warn!(target: "Config", "Cannot read the api config, using the default one: {e}");
+                Self::default()
+            }
+        }
+    }
+
+    /// Get the allowed origins as header values, the invalid ones are ignored with a warning
+    pub(crate) fn allowed_origins(&self) -> Vec<HeaderValue> {
+        self.allowed_origins.iter()
+            .filter_map(|origin| match HeaderValue::from_str(origin.trim_end_matches('/')) {
+                Ok(origin) => Some(origin),
+                Err(_) => {
+                    warn!(target: "Config", "Ignoring the invalid allowed origin '{origin}'");
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Get the key used to sign the cookies
+    ///
+    /// Without a configured secret, the key is random and the signed cookies will not survive a restart
+    pub(crate) fn cookie_key(&self) -> Vec<u8> {
+        if let Some(key) = self.cookie_secret.as_ref().and_then(|secret| hex::decode(secret).ok()) {
+            return key
+        }
+
+        warn!(target: "Config", "No valid cookie secret configured, using a random one");
+        let mut key = vec![0u8; 32];
+        rand::thread_rng().fill_bytes(&mut key);
+        key
+    }
+}
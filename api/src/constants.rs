@@ -1,3 +1,51 @@
-pub(crate) const TEST_USER_ID: &str = "251ef68e-6d10-4e08-a8e9-d4f42ad9fe36";
+pub(crate) const PRODUCTS_PER_PAGE: u64 = 5;
+pub(crate) const REVIEWS_PER_PAGE: u64 = 10;
+/// Highest `page_size` a client can ask for
+pub(crate) const MAX_PAGE_SIZE: u64 = 50;
+pub(crate) const SEARCH_QUERY_MAX_LENGTH: usize = 256;
 
-pub(crate) const PRODUCTS_PER_PAGE: u64 = 5;
\ No newline at end of file
+/// Same sizes as the columns of the `product` table
+pub(crate) const PRODUCT_NAME_MAX_LENGTH: usize = 256;
+pub(crate) const PRODUCT_DESCRIPTION_MAX_LENGTH: usize = 2048;
+/// Same sizes as the columns of the `product_characteristic` table
+pub(crate) const CHARACTERISTIC_NAME_MAX_LENGTH: usize = 128;
+pub(crate) const CHARACTERISTIC_DETAIL_MAX_LENGTH: usize = 256;
+/// Same sizes as the columns of the `category` table
+pub(crate) const CATEGORY_NAME_MAX_LENGTH: usize = 128;
+pub(crate) const CATEGORY_SLUG_MAX_LENGTH: usize = 64;
+/// Same sizes as the columns of the variant tables
+pub(crate) const VARIANT_OPTION_MAX_LENGTH: usize = 64;
+pub(crate) const VARIANT_VALUE_MAX_LENGTH: usize = 64;
+pub(crate) const SKU_MAX_LENGTH: usize = 64;
+/// Same size as the `reviews.review` column
+pub(crate) const REVIEW_MAX_LENGTH: usize = 512;
+pub(crate) const REVIEW_MIN_STARS: u8 = 1;
+pub(crate) const REVIEW_MAX_STARS: u8 = 5;
+/// Same size as the `reviews.rejection_reason` column
+pub(crate) const REJECTION_REASON_MAX_LENGTH: usize = 256;
+/// Same size as the `product_image.alt` column
+pub(crate) const IMAGE_ALT_MAX_LENGTH: usize = 256;
+/// Largest image file accepted by the upload (10 MiB)
+pub(crate) const IMAGE_MAX_SIZE: usize = 10 * 1024 * 1024;
+/// Widths of the resized versions of the product images, the narrowest first
+pub(crate) const IMAGE_WIDTHS: [u32; 3] = [320, 640, 1280];
+
+/// Same size as the `clients.username` column
+pub(crate) const USERNAME_MAX_LENGTH: usize = 32;
+pub(crate) const PASSWORD_MIN_LENGTH: usize = 8;
+/// Same size as the `clients.email` column
+pub(crate) const EMAIL_MAX_LENGTH: usize = 254;
+/// A password reset link can be used during this duration (1 hour)
+pub(crate) const PASSWORD_RESET_DURATION_SECONDS: u64 = 60 * 60;
+
+/// Name of the cookie holding the session token of a client
+pub(crate) const SESSION_COOKIE: &str = "pedalize_session";
+
+/// A session expires after this duration without any request (30 days)
+pub(crate) const SESSION_DURATION_SECONDS: u64 = 30 * 24 * 60 * 60;
+/// Same size as the `sessions.user_agent` column
+pub(crate) const USER_AGENT_MAX_LENGTH: usize = 256;
+
+/// Name of the cookie holding the signed id of an anonymous visitor
+pub(crate) const GUEST_COOKIE: &str = "pedalize_guest";
+pub(crate) const GUEST_COOKIE_DURATION_SECONDS: u64 = 30 * 24 * 60 * 60;
\ No newline at end of file
@@ -60,4 +60,22 @@ impl Database {
     pub(crate) async fn get_pool(&self) -> RwLockReadGuard<'_, MySqlPool> {
         self.pool.read().await
     }
-}
\ No newline at end of file
+}
+/// Implement `sqlx::Type` for an enum deriving `sqlx::Encode` and `sqlx::Decode`, stored in an `ENUM` or `CHAR` column
+///
+/// The server reports these columns as strings, which the derived `sqlx::Type` refuses, so the enum is typed as a string
+macro_rules! string_enum_type {
+    ($enum:ty) => {
+        impl sqlx::Type<sqlx::MySql> for $enum {
+            fn type_info() -> sqlx::mysql::MySqlTypeInfo {
+                <str as sqlx::Type<sqlx::MySql>>::type_info()
+            }
+
+            fn compatible(ty: &sqlx::mysql::MySqlTypeInfo) -> bool {
+                <str as sqlx::Type<sqlx::MySql>>::compatible(ty)
+            }
+        }
+    };
+}
+
+pub(crate) use string_enum_type;
@@ -1,4 +1,7 @@
-use serde::{Deserialize, Serialize};
+use axum::http::StatusCode;
+use axum::Json;
+use axum::response::{IntoResponse, Response};
+use serde::{Deserialize, Deserializer, Serialize};
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct ApiError {
@@ -13,4 +16,20 @@ impl ApiError {
             message: message.to_string()
         }
     }
-}
\ No newline at end of file
+}
+/// Build the response refusing an invalid form, with the 422 status
+pub(crate) fn unprocessable(e: ApiError) -> Response {
+    (StatusCode::UNPROCESSABLE_ENTITY, Json(e)).into_response()
+}
+
+/// Deserialize a field which can be absent (unchanged) or `null` (cleared)
+pub(crate) fn double_option<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+    where D: Deserializer<'de>, T: Deserialize<'de>
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+/// Whether the query was refused by a `UNIQUE` constraint
+pub(crate) fn is_unique_violation(e: &sqlx::Error) -> bool {
+    e.as_database_error().is_some_and(|e| e.is_unique_violation())
+}
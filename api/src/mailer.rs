@@ -0,0 +1,99 @@
+use std::error::Error;
+use std::path::PathBuf;
+use axum::async_trait;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+use tracing::info;
+use uuid::Uuid;
+
+/// An email ready to be sent
+pub(crate) struct Mail {
+    pub to: String,
+    pub subject: String,
+    pub body: String
+}
+
+/// Send the emails of the API, the implementation is chosen by the `mailer` entry of the api config
+#[async_trait]
+pub(crate) trait Mailer: Send + Sync {
+    async fn send(&self, mail: Mail) -> Result<(), Box<dyn Error + Send + Sync>>;
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub(crate) enum MailerConfig {
+    Smtp {
+        host: String,
+        port: u16,
+        username: String,
+        password: String,
+        from: String
+    },
+    /// Write every email in a directory instead of sending it, for the development and the tests
+    File {
+        directory: PathBuf
+    }
+}
+
+impl Default for MailerConfig {
+    fn default() -> Self {
+        Self::File { directory: PathBuf::from("mails") }
+    }
+}
+
+impl MailerConfig {
+    pub(crate) fn build(&self) -> Result<Box<dyn Mailer>, Box<dyn Error>> {
+        Ok(match self {
+            Self::Smtp { host, port, username, password, from } => {
+                let transport = AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)?
+                    .port(*port)
+                    .credentials(Credentials::new(username.clone(), password.clone()))
+                    .build();
+
+                Box::new(SmtpMailer { transport, from: from.parse()? })
+            }
+            Self::File { directory } => Box::new(FileMailer { directory: directory.clone() })
+        })
+    }
+}
+
+pub(crate) struct SmtpMailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox
+}
+
+#[async_trait]
+impl Mailer for SmtpMailer {
+    async fn send(&self, mail: Mail) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(mail.to.parse()?)
+            .subject(mail.subject)
+            .body(mail.body)?;
+
+        self.transport.send(message).await?;
+        Ok(())
+    }
+}
+
+/// Write each email in its own file of the directory, and log it
+pub(crate) struct FileMailer {
+    directory: PathBuf
+}
+
+#[async_trait]
+impl Mailer for FileMailer {
+    async fn send(&self, mail: Mail) -> Result<(), Box<dyn Error + Send + Sync>> {
+        fs::create_dir_all(&self.directory).await?;
+
+        let path = self.directory.join(format!("{}.eml", Uuid::new_v4()));
+        let content = format!("To: {}\nSubject: {}\n\n{}\n", mail.to, mail.subject, mail.body);
+        fs::write(&path, content).await?;
+
+        info!(target: "Mailer", "Mail '{}' to {} written in {}", mail.subject, mail.to, path.display());
+        Ok(())
+    }
+}
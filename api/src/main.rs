@@ -1,17 +1,33 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use axum::http::header::{AUTHORIZATION, CONTENT_TYPE};
 use axum::http::Method;
 use axum::Router;
-use axum::routing::{delete, get, patch, post};
-use tower_http::cors::{Any, CorsLayer};
+use axum::extract::DefaultBodyLimit;
+use axum::middleware;
+use axum::routing::{delete, get, patch, post, put};
+use tower_http::cors::CorsLayer;
 use tower_http::trace::TraceLayer;
-use tracing::info;
+use tracing::{error, info};
+use crate::config::Config;
+use crate::constants::IMAGE_MAX_SIZE;
 use crate::database::Database;
+use crate::mailer::Mailer;
+use crate::rate_limit::RateLimiter;
 
 mod declaration;
 mod constants;
+mod config;
+mod mailer;
+mod money;
+mod pagination;
+mod rate_limit;
 mod database;
 mod shopping_cart;
 mod clients;
 mod product;
+mod category;
 
 #[tokio::main]
 async fn main() {
@@ -22,36 +38,166 @@ async fn main() {
     // init database
     let database = Database::init().await;
 
-    let app_state = AppState { database };
+    let config = Config::init();
 
+    let mailer = match config.mailer.build() {
+        Ok(m) => m,
+        Err(e) => {
+            error!(target: "App", "Cannot build the mailer: {e:#?}");
+            panic!("Unable to build the mailer");
+        }
+    };
 
-    let app = Router::new()
+    let rate_limiter = Arc::new(RateLimiter::new(config.rate_limit.clone()));
+
+    // forget the idle clients of the rate limiter from time to time
+    let pruned_limiter = rate_limiter.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(60));
+        loop {
+            interval.tick().await;
+            pruned_limiter.prune();
+        }
+    });
+
+    let app_state = AppState {
+        database,
+        cookie_key: Arc::new(config.cookie_key()),
+        mailer: Arc::from(mailer),
+        rate_limiter,
+        config: Arc::new(config)
+    };
+
+
+    let shopping_cart_routes = Router::new()
         .route("/shopping_cart/fetch", get(shopping_cart::routes::get_shopping_cart))
         .route("/shopping_cart/articles/add", post(shopping_cart::routes::add_article_to_shopping_cart))
         .route("/shopping_cart/articles/change_quantity", patch(shopping_cart::routes::edit_article_quantity))
         .route("/shopping_cart/articles/remove", delete(shopping_cart::routes::delete_article))
         .route("/shopping_cart/clear", delete(shopping_cart::routes::clear_articles))
+        .route_layer(middleware::from_fn_with_state(app_state.clone(), shopping_cart::guest::guest_layer));
 
+    // every management route lives under /admin and requires at least the staff role
+    let admin_routes = Router::new()
+        .route("/clients", get(clients::routes::get_all_clients))
+        .route("/clients/:id/role", patch(clients::routes::set_client_role))
+        .route("/reviews", get(product::moderation::routes::get_moderation_queue))
+        .route("/reviews/:id/approve", post(product::moderation::routes::approve_review))
+        .route("/reviews/:id/reject", post(product::moderation::routes::reject_review))
+        .route_layer(middleware::from_fn_with_state(app_state.clone(), clients::role::staff_layer));
+
+    let app = Router::new()
+        .merge(shopping_cart_routes)
+        .nest("/admin", admin_routes)
+
+        .route("/clients/register", post(clients::routes::register))
+        .route("/clients/login", post(clients::routes::login))
+        .route("/clients/logout", post(clients::routes::logout))
+        .route("/clients/password/forgot", post(clients::routes::forgot_password))
+        .route("/clients/password/reset", post(clients::routes::reset_password))
+        .route("/clients/me", get(clients::routes::get_me))
+        .route("/clients/me", patch(clients::routes::edit_me))
+        .route("/clients/me", delete(clients::routes::delete_me))
+        .route("/clients/me/password", post(clients::routes::change_password))
+        .route("/clients/me/export", get(clients::routes::export_me))
+        .route("/clients/me/sessions", get(clients::routes::get_sessions))
+        .route("/clients/me/sessions", delete(clients::routes::revoke_sessions))
+
+        .route("/product", post(product::routes::create_product))
         .route("/product/all", get(product::routes::get_all_products))
         .route("/product/page", get(product::routes::get_product_page))
-        .route("/product/:id", get(product::routes::get_product_detail))
-        .route("/product/:id/characteristics", get(product::routes::get_product_characteristics))
+        .route("/product/search", get(product::routes::search_products))
+        .route(
+            "/product/:id",
+            get(product::routes::get_product_detail)
+                .put(product::routes::replace_product)
+                .patch(product::routes::edit_product)
+                .delete(product::routes::delete_product)
+        )
+        .route(
+            "/product/:id/characteristics",
+            get(product::routes::get_product_characteristics)
+                .post(product::routes::add_product_characteristic)
+                .put(product::routes::replace_product_characteristics)
+        )
+        .route("/product/:id/characteristics/order", put(product::routes::reorder_product_characteristics))
+        .route(
+            "/product/:id/characteristics/:name",
+            patch(product::routes::edit_product_characteristic)
+                .delete(product::routes::delete_product_characteristic)
+        )
+        .route(
+            "/product/:id/variants",
+            get(product::variant::routes::get_product_variants)
+                .post(product::variant::routes::create_product_variant)
+        )
+        .route(
+            "/product/:id/variants/:variant",
+            patch(product::variant::routes::edit_product_variant)
+                .delete(product::variant::routes::delete_product_variant)
+        )
+        .route("/product/:id/options", put(product::variant::routes::replace_product_options))
+        .route(
+            "/product/:id/images",
+            post(product::image::routes::upload_product_image)
+                .layer(DefaultBodyLimit::max(IMAGE_MAX_SIZE))
+        )
+        .route("/product/:id/images/order", put(product::image::routes::reorder_product_images))
+        .route(
+            "/product/:id/images/:hash",
+            patch(product::image::routes::edit_product_image)
+                .delete(product::image::routes::delete_product_image)
+        )
+        .route("/images/:hash", get(product::image::routes::get_image))
+        .route("/images/:hash/:width", get(product::image::routes::get_resized_image))
         .route("/product/:id/reviews", get(product::routes::get_product_reviews))
         .route("/product/:id/reviews", post(product::routes::new_review))
+        .route("/product/:id/reviews/summary", get(product::routes::get_product_review_summary))
+        .route(
+            "/product/:id/reviews/:review_id",
+            put(product::routes::edit_review)
+                .delete(product::routes::delete_review)
+        )
+
+        .route("/category", get(category::routes::get_categories).post(category::routes::create_category))
+        .route(
+            "/category/:slug",
+            patch(category::routes::edit_category)
+                .delete(category::routes::delete_category)
+        )
+        .route(
+            "/category/:slug/products",
+            get(category::routes::get_category_products)
+                .post(category::routes::add_category_product)
+        )
+        .route("/category/:slug/products/:product", delete(category::routes::remove_category_product))
 
         .route("/test", get(|| async { "Hello, World!" }))
-        .layer(CorsLayer::new().allow_origin(Any).allow_methods([Method::GET, Method::DELETE, Method::POST]))
+        .layer(middleware::from_fn_with_state(app_state.clone(), rate_limit::rate_limit_layer))
+        // the credentials let the browser send the session and guest cookies, which requires explicit origins
+        .layer(
+            CorsLayer::new()
+                .allow_origin(app_state.config.allowed_origins())
+                .allow_credentials(true)
+                .allow_methods([Method::GET, Method::DELETE, Method::POST, Method::PUT, Method::PATCH])
+                .allow_headers([AUTHORIZATION, CONTENT_TYPE])
+        )
         .layer(TraceLayer::new_for_http())
         .with_state(app_state);
 
     info!(target: "App", "Running on 0.0.0.0:9999");
     axum::Server::bind(&"0.0.0.0:9999".parse().unwrap())
-        .serve(app.into_make_service())
+        .serve(app.into_make_service_with_connect_info::<SocketAddr>())
         .await
         .unwrap();
 }
 
 #[derive(Clone)]
 pub struct AppState {
-    database: Database
+    database: Database,
+    config: Arc<Config>,
+    /// Key signing the cookies, see [`Config::cookie_key`]
+    cookie_key: Arc<Vec<u8>>,
+    mailer: Arc<dyn Mailer>,
+    rate_limiter: Arc<RateLimiter>
 }
\ No newline at end of file
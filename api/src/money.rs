@@ -0,0 +1,70 @@
+use std::fmt::{Display, Formatter};
+use serde::{Deserialize, Serialize, Serializer};
+use serde::ser::SerializeStruct;
+use crate::database::string_enum_type;
+
+/// Currency of an amount, stored as its ISO 4217 code
+#[derive(sqlx::Encode, sqlx::Decode, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+#[sqlx(rename_all = "UPPERCASE")]
+#[serde(rename_all = "UPPERCASE")]
+pub(crate) enum Currency {
+    #[default]
+    Eur,
+    Usd,
+    Gbp
+}
+
+string_enum_type!(Currency);
+
+impl Currency {
+    fn symbol(&self) -> &'static str {
+        match self {
+            Self::Eur => "€",
+            Self::Usd => "$",
+            Self::Gbp => "£"
+        }
+    }
+}
+
+/// An amount of money in minor units (cents), never a floating point number
+///
+/// Serialized as `{"cents": 7999, "currency": "EUR", "formatted": "79,99 €"}`
+#[derive(sqlx::FromRow, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) struct Money {
+    #[sqlx(rename = "price")]
+    pub cents: u64,
+    pub currency: Currency
+}
+
+impl Money {
+    pub(crate) fn new(cents: u64, currency: Currency) -> Self {
+        Self { cents, currency }
+    }
+
+    /// Price of `quantity` times this amount, saturating instead of overflowing
+    pub(crate) fn saturating_mul(&self, quantity: u64) -> Self {
+        Self::new(self.cents.saturating_mul(quantity), self.currency)
+    }
+}
+
+impl Display for Money {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let units = self.cents / 100;
+        let cents = self.cents % 100;
+
+        match self.currency {
+            Currency::Eur => write!(f, "{units},{cents:02} {}", self.currency.symbol()),
+            Currency::Usd | Currency::Gbp => write!(f, "{}{units}.{cents:02}", self.currency.symbol())
+        }
+    }
+}
+
+impl Serialize for Money {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("Money", 3)?;
+        state.serialize_field("cents", &self.cents)?;
+        state.serialize_field("currency", &self.currency)?;
+        state.serialize_field("formatted", &self.to_string())?;
+        state.end()
+    }
+}
@@ -0,0 +1,113 @@
+use serde::{Deserialize, Serialize};
+use crate::constants::MAX_PAGE_SIZE;
+use crate::declaration::ApiError;
+
+/// Envelope of every paginated response
+#[derive(Serialize, Deserialize, Clone)]
+pub(crate) struct Page<T> {
+    pub items: Vec<T>,
+    /// `None` when the page was requested with a cursor
+    pub page: Option<u64>,
+    pub page_size: u64,
+    /// Number of items matching the request, on every page
+    pub total: u64,
+    /// Give it back as the `cursor` query parameter to get the next page, `None` on the last page
+    pub next_cursor: Option<String>
+}
+
+/// Position of a page, read from the `page`, `page_size` and `cursor` query parameters
+///
+/// The cursor, when there is one, takes precedence over the page number
+pub(crate) struct Pagination {
+    pub page: u64,
+    pub page_size: u64,
+    pub cursor: Option<Cursor>
+}
+
+impl Pagination {
+    /// The page size is capped to [`MAX_PAGE_SIZE`]
+    pub(crate) fn new(page: u64, page_size: Option<u64>, default_page_size: u64, cursor: Option<&str>) -> Result<Self, ApiError> {
+        let cursor = match cursor.filter(|c| !c.is_empty()) {
+            Some(cursor) => Some(Cursor::decode(cursor).ok_or_else(invalid_cursor)?),
+            None => None
+        };
+
+        Ok(Self {
+            page,
+            page_size: page_size.unwrap_or(default_page_size).clamp(1, MAX_PAGE_SIZE),
+            cursor
+        })
+    }
+
+    /// Refuse a cursor without a sort key, for the listings whose sort key is never null
+    pub(crate) fn require_cursor_key(self) -> Result<Self, ApiError> {
+        match &self.cursor {
+            Some(Cursor { key: None, .. }) => Err(invalid_cursor()),
+            _ => Ok(self)
+        }
+    }
+
+    /// Number of items to skip, always 0 with a cursor since the cursor already filters them out
+    pub(crate) fn offset(&self) -> u64 {
+        match self.cursor {
+            Some(_) => 0,
+            None => self.page.saturating_mul(self.page_size)
+        }
+    }
+
+    /// Build the page from the fetched rows, one more row than the page size must have been fetched
+    /// to know whether there is a next page
+    ///
+    /// `cursor_of` builds the cursor pointing after an item
+    pub(crate) fn into_page<T>(self, mut items: Vec<T>, total: u64, cursor_of: impl Fn(&T) -> Cursor) -> Page<T> {
+        let has_next = items.len() as u64 > self.page_size;
+        items.truncate(self.page_size as usize);
+
+        Page {
+            next_cursor: items.last().filter(|_| has_next).map(|item| cursor_of(item).encode()),
+            page: match self.cursor {
+                Some(_) => None,
+                None => Some(self.page)
+            },
+            page_size: self.page_size,
+            total,
+            items
+        }
+    }
+
+    /// Same as [`Pagination::into_page`], for the listings without a stable order to resume from
+    ///
+    /// There is never a next cursor, the next page is requested with its number
+    pub(crate) fn into_offset_page<T>(self, mut items: Vec<T>, total: u64) -> Page<T> {
+        items.truncate(self.page_size as usize);
+
+        Page {
+            items,
+            page: Some(self.page),
+            page_size: self.page_size,
+            total,
+            next_cursor: None
+        }
+    }
+}
+
+fn invalid_cursor() -> ApiError {
+    ApiError::new(1002, "Invalid cursor")
+}
+
+/// Keyset position after the last item of a page: the value of its sort key and its id
+#[derive(Serialize, Deserialize, Clone)]
+pub(crate) struct Cursor {
+    pub key: Option<String>,
+    pub id: String
+}
+
+impl Cursor {
+    fn encode(&self) -> String {
+        hex::encode(serde_json::to_vec(self).unwrap_or_default())
+    }
+
+    fn decode(cursor: &str) -> Option<Self> {
+        serde_json::from_slice(hex::decode(cursor).ok()?.as_slice()).ok()
+    }
+}
@@ -0,0 +1,651 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use ::image::imageops::FilterType;
+use ::image::DynamicImage;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::{MySql, MySqlPool, QueryBuilder};
+use uuid::Uuid;
+use crate::constants::{IMAGE_ALT_MAX_LENGTH, IMAGE_WIDTHS};
+use crate::declaration::ApiError;
+use crate::product::ProductId;
+
+/// Format of an image, recognized from the first bytes of the file rather than its name
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum ImageFormat {
+    Png,
+    Jpeg,
+    Webp
+}
+
+impl ImageFormat {
+    fn detect(bytes: &[u8]) -> Option<Self> {
+        if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+            Some(Self::Png)
+        } else if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+            Some(Self::Jpeg)
+        } else if bytes.len() >= 12 && bytes.starts_with(b"RIFF") && &bytes[8..12] == b"WEBP" {
+            Some(Self::Webp)
+        } else {
+            None
+        }
+    }
+
+    fn from_extension(extension: &str) -> Option<Self> {
+        match extension {
+            "png" => Some(Self::Png),
+            "jpg" => Some(Self::Jpeg),
+            "webp" => Some(Self::Webp),
+            _ => None
+        }
+    }
+
+    pub(crate) fn extension(&self) -> &'static str {
+        match self {
+            Self::Png => "png",
+            Self::Jpeg => "jpg",
+            Self::Webp => "webp"
+        }
+    }
+
+    pub(crate) fn mime(&self) -> &'static str {
+        match self {
+            Self::Png => "image/png",
+            Self::Jpeg => "image/jpeg",
+            Self::Webp => "image/webp"
+        }
+    }
+
+    fn codec(&self) -> ::image::ImageFormat {
+        match self {
+            Self::Png => ::image::ImageFormat::Png,
+            Self::Jpeg => ::image::ImageFormat::Jpeg,
+            Self::Webp => ::image::ImageFormat::WebP
+        }
+    }
+}
+
+/// A resized version of an image, generated and stored on its first request
+#[derive(Serialize, Deserialize, Clone)]
+pub(crate) struct ImageSize {
+    /// An image narrower than this width keeps its own width, it is never enlarged
+    pub width: u32,
+    /// Same format as the original image
+    pub url: String,
+    pub webp_url: String
+}
+
+/// An image of the gallery of a product
+///
+/// The file is stored once on the disk, named after the SHA-256 of its content, even if several products use it
+#[derive(sqlx::FromRow, Serialize, Deserialize, Clone)]
+pub(crate) struct ProductImage {
+    #[serde(skip)]
+    pub product: ProductId,
+    pub hash: String,
+    pub alt: Option<String>,
+    pub position: u32,
+    /// Address the original image is served at
+    #[sqlx(skip)]
+    #[serde(default)]
+    pub url: String,
+    /// The resized versions, the narrowest first
+    #[sqlx(skip)]
+    #[serde(default)]
+    pub sizes: Vec<ImageSize>
+}
+
+const GET_IMAGES_QUERY: &str = "SELECT product, hash, alt, position FROM product_image";
+
+impl ProductImage {
+    /// Fill the addresses of the image and of its resized versions
+    fn with_urls(mut self) -> Self {
+        self.url = format!("/images/{}", self.hash);
+        self.sizes = IMAGE_WIDTHS.iter()
+            .map(|width| ImageSize {
+                width: *width,
+                url: format!("/images/{}/{width}", self.hash),
+                webp_url: format!("/images/{}/{width}.webp", self.hash)
+            })
+            .collect();
+        self
+    }
+
+    pub(crate) async fn get_of_product(pool: &MySqlPool, product: &str) -> Result<Vec<Self>, sqlx::Error> {
+        let images = sqlx::query_as::<_, Self>(format!("{GET_IMAGES_QUERY} WHERE product = ? ORDER BY position, hash").as_str())
+            .bind(product)
+            .fetch_all(pool)
+            .await?;
+
+        Ok(images.into_iter().map(Self::with_urls).collect())
+    }
+
+    /// Fetch the galleries of several products in one query, keyed by product
+    pub(crate) async fn get_of_products(pool: &MySqlPool, products: &[ProductId]) -> Result<HashMap<ProductId, Vec<Self>>, sqlx::Error> {
+        let mut galleries: HashMap<ProductId, Vec<Self>> = HashMap::new();
+        if products.is_empty() {
+            return Ok(galleries)
+        }
+
+        let mut builder: QueryBuilder<MySql> = QueryBuilder::new(GET_IMAGES_QUERY);
+        builder.push(" WHERE product IN (");
+        let mut ids = builder.separated(", ");
+        for product in products {
+            ids.push_bind(product.clone());
+        }
+        builder.push(") ORDER BY position, hash");
+
+        let images = builder.build_query_as::<Self>()
+            .fetch_all(pool)
+            .await?;
+
+        for image in images {
+            galleries.entry(image.product.clone()).or_default().push(image.with_urls());
+        }
+
+        Ok(galleries)
+    }
+
+    async fn find(pool: &MySqlPool, product: &str, hash: &str) -> Result<Option<Self>, sqlx::Error> {
+        let image = sqlx::query_as::<_, Self>(format!("{GET_IMAGES_QUERY} WHERE product = ? AND hash = ?").as_str())
+            .bind(product)
+            .bind(hash)
+            .fetch_optional(pool)
+            .await?;
+
+        Ok(image.map(Self::with_urls))
+    }
+
+    /// Format of a stored file, `None` if no product uses it
+    async fn format_of(pool: &MySqlPool, hash: &str) -> Result<Option<ImageFormat>, sqlx::Error> {
+        let extension: Option<String> = sqlx::query_scalar("SELECT extension FROM product_image WHERE hash = ? LIMIT 1")
+            .bind(hash)
+            .fetch_optional(pool)
+            .await?;
+
+        Ok(extension.as_deref().and_then(ImageFormat::from_extension))
+    }
+
+    /// Check the alternative text against the constraints of the `product_image` table
+    fn validate_alt(alt: Option<&str>) -> Result<(), ApiError> {
+        if alt.is_some_and(|a| a.chars().count() > IMAGE_ALT_MAX_LENGTH) {
+            return Err(ApiError::new(4049, format!("The alternative text must contain at most {IMAGE_ALT_MAX_LENGTH} characters")))
+        }
+        Ok(())
+    }
+
+    /// Add the image at the end of the gallery of the product
+    async fn add(pool: &MySqlPool, product: &str, hash: &str, format: ImageFormat, alt: Option<&str>) -> Result<Self, sqlx::Error> {
+        let last: Option<u32> = sqlx::query_scalar("SELECT MAX(position) FROM product_image WHERE product = ?")
+            .bind(product)
+            .fetch_one(pool)
+            .await?;
+        let position = last.map_or(0, |p| p + 1);
+
+        sqlx::query("INSERT INTO product_image (product, hash, extension, alt, position) VALUE (?, ?, ?, ?, ?)")
+            .bind(product)
+            .bind(hash)
+            .bind(format.extension())
+            .bind(alt)
+            .bind(position)
+            .execute(pool)
+            .await?;
+
+        let image = Self {
+            product: product.to_string(),
+            hash: hash.to_string(),
+            alt: alt.map(|a| a.to_string()),
+            position,
+            url: String::new(),
+            sizes: Vec::new()
+        };
+
+        Ok(image.with_urls())
+    }
+
+    async fn update_alt(pool: &MySqlPool, product: &str, hash: &str, alt: Option<&str>) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE product_image SET alt = ? WHERE product = ? AND hash = ?")
+            .bind(alt)
+            .bind(product)
+            .bind(hash)
+            .execute(pool)
+            .await
+            .map(|_| ())
+    }
+
+    async fn delete(pool: &MySqlPool, product: &str, hash: &str) -> Result<bool, sqlx::Error> {
+        sqlx::query("DELETE FROM product_image WHERE product = ? AND hash = ?")
+            .bind(product)
+            .bind(hash)
+            .execute(pool)
+            .await
+            .map(|res| res.rows_affected() > 0)
+    }
+
+    /// Give each image its index in `hashes` as position, in one transaction
+    ///
+    /// Return `false`, without changing anything, if `hashes` is not exactly the list of the images of the product
+    async fn reorder(pool: &MySqlPool, product: &str, hashes: &[String]) -> Result<bool, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        let mut existing: Vec<String> = sqlx::query_scalar("SELECT hash FROM product_image WHERE product = ? FOR UPDATE")
+            .bind(product)
+            .fetch_all(&mut *tx)
+            .await?;
+
+        let mut wanted = hashes.to_vec();
+        existing.sort();
+        wanted.sort();
+        if existing != wanted {
+            return Ok(false)
+        }
+
+        for (position, hash) in hashes.iter().enumerate() {
+            sqlx::query("UPDATE product_image SET position = ? WHERE product = ? AND hash = ?")
+                .bind(position as u32)
+                .bind(product)
+                .bind(hash)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await.map(|_| true)
+    }
+}
+
+/// Hex encoded SHA-256 of the content, the name of the stored file
+fn content_hash(bytes: &[u8]) -> String {
+    hex::encode(Sha256::digest(bytes))
+}
+
+/// Reject anything but a hash, the value ends up in a path
+fn is_hash(value: &str) -> bool {
+    value.len() == 64 && value.bytes().all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b))
+}
+
+fn file_path(dir: &str, hash: &str, format: ImageFormat) -> PathBuf {
+    Path::new(dir).join(format!("{hash}.{}", format.extension()))
+}
+
+fn resized_path(dir: &str, hash: &str, width: u32, format: ImageFormat) -> PathBuf {
+    Path::new(dir).join(format!("{hash}_{width}.{}", format.extension()))
+}
+
+/// Write the file under a temporary name first, a partially written file is never served
+///
+/// The temporary name is unique, so concurrent writes of the same file or of its other formats never mix their bytes.
+/// The content of a file is fixed by its name, so a file written by another request in the meantime is kept
+async fn write_file(dir: &str, path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    tokio::fs::create_dir_all(dir).await?;
+
+    let name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+    let partial = path.with_file_name(format!("{name}.{}.part", Uuid::new_v4()));
+    tokio::fs::write(&partial, bytes).await?;
+
+    match tokio::fs::rename(&partial, path).await {
+        Ok(()) => Ok(()),
+        Err(_) if tokio::fs::try_exists(path).await.unwrap_or(false) => {
+            let _ = tokio::fs::remove_file(&partial).await;
+            Ok(())
+        }
+        Err(e) => {
+            let _ = tokio::fs::remove_file(&partial).await;
+            Err(e)
+        }
+    }
+}
+
+/// Write the file unless the same content is already stored
+async fn store(dir: &str, hash: &str, format: ImageFormat, bytes: &[u8]) -> std::io::Result<()> {
+    let path = file_path(dir, hash, format);
+    if tokio::fs::try_exists(&path).await? {
+        return Ok(())
+    }
+
+    write_file(dir, &path, bytes).await
+}
+
+/// Whether the whole image decodes, the detection of its format only reads its first bytes
+fn decodes(bytes: &[u8], format: ImageFormat) -> bool {
+    ::image::load_from_memory_with_format(bytes, format.codec()).is_ok()
+}
+
+/// Decode the image, shrink it to the width if it is wider, keeping its ratio, and encode it in the format
+fn resize(bytes: &[u8], source: ImageFormat, width: u32, target: ImageFormat) -> ::image::ImageResult<Vec<u8>> {
+    let mut image = ::image::load_from_memory_with_format(bytes, source.codec())?;
+    if image.width() > width {
+        image = image.resize(width, u32::MAX, FilterType::Lanczos3);
+    }
+    // the WebP encoder only takes 8 bits RGB(A) images
+    if target == ImageFormat::Webp {
+        image = DynamicImage::ImageRgba8(image.to_rgba8());
+    }
+
+    let mut encoded = Cursor::new(Vec::new());
+    image.write_to(&mut encoded, target.codec())?;
+    Ok(encoded.into_inner())
+}
+
+/// Get the resized version of a stored image, generated and stored on the first request
+async fn resized(dir: &str, hash: &str, source: ImageFormat, width: u32, target: ImageFormat) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+    let path = resized_path(dir, hash, width, target);
+    if tokio::fs::try_exists(&path).await? {
+        return Ok(tokio::fs::read(&path).await?)
+    }
+
+    let original = tokio::fs::read(file_path(dir, hash, source)).await?;
+    // decoding and encoding are CPU bound, they must not block the runtime
+    let bytes = tokio::task::spawn_blocking(move || resize(&original, source, width, target)).await??;
+
+    write_file(dir, &path, &bytes).await?;
+    Ok(bytes)
+}
+
+/// Contain every routes for axum
+pub(crate) mod routes {
+    use std::ops::Deref;
+    use axum::extract::{Multipart, Path, State};
+    use axum::{Form, Json};
+    use axum::http::{header, StatusCode};
+    use axum::response::{IntoResponse, Response};
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+    use tracing::error;
+    use crate::AppState;
+    use crate::clients::role::Admin;
+    use crate::declaration::{ApiError, unprocessable};
+    use crate::product::routes::fetch_product;
+    use crate::constants::IMAGE_WIDTHS;
+    use crate::product::image::{content_hash, decodes, file_path, is_hash, resized, store, ImageFormat, ProductImage};
+
+    fn image_not_found() -> Response {
+        (StatusCode::NOT_FOUND, Json(ApiError::new(4044, "This image does not exist"))).into_response()
+    }
+
+    /// Response serving an image file, the content never changes for a given address
+    fn image_file(format: ImageFormat, bytes: Vec<u8>) -> Response {
+        (
+            [
+                (header::CONTENT_TYPE, format.mime()),
+                (header::CACHE_CONTROL, "public, max-age=31536000, immutable")
+            ],
+            bytes
+        ).into_response()
+    }
+
+    /// An empty alternative text is no alternative text
+    fn clean_alt(alt: Option<String>) -> Option<String> {
+        alt.map(|a| a.trim().to_string()).filter(|a| !a.is_empty())
+    }
+
+    /// Route: POST /product/:id/images
+    ///
+    /// Add an image at the end of the gallery of a product, restricted to the admins
+    ///
+    /// The multipart body has an `image` file field, PNG, JPEG or WebP, and an optional `alt` text field
+    pub(crate) async fn upload_product_image(
+        State(app_state): State<AppState>,
+        Path(product): Path<String>,
+        _: Admin,
+        mut multipart: Multipart
+    ) -> impl IntoResponse
+    {
+        let mut image = None;
+        let mut alt = None;
+
+        loop {
+            let field = match multipart.next_field().await {
+                Ok(Some(field)) => field,
+                Ok(None) => break,
+                Err(e) => return (e.status(), Json(ApiError::new(4046, e.body_text()))).into_response()
+            };
+
+            let res = match field.name() {
+                Some("image") => field.bytes().await.map(|bytes| image = Some(bytes)),
+                Some("alt") => field.text().await.map(|text| alt = Some(text)),
+                _ => Ok(())
+            };
+            if let Err(e) = res {
+                return (e.status(), Json(ApiError::new(4046, e.body_text()))).into_response()
+            }
+        }
+
+        let Some(image) = image.filter(|i| !i.is_empty()) else {
+            return unprocessable(ApiError::new(4047, "The image file is missing"))
+        };
+        let Some(format) = ImageFormat::detect(&image) else {
+            return unprocessable(ApiError::new(4048, "The image must be a PNG, JPEG or WebP file"))
+        };
+        let alt = clean_alt(alt);
+        if let Err(e) = ProductImage::validate_alt(alt.as_deref()) {
+            return unprocessable(e)
+        }
+
+        // a truncated or corrupted file would only fail once resized, decoding is CPU bound like the resizing
+        let bytes = image.clone();
+        match tokio::task::spawn_blocking(move || decodes(&bytes, format)).await {
+            Ok(true) => {},
+            Ok(false) => return unprocessable(ApiError::new(4069, "The image file is corrupted or incomplete")),
+            Err(e) => {
+                error!(target: "UploadProductImage", "Cannot decode the image: {e:#?}");
+                return Json(ApiError::new(4045, "Cannot add the image")).into_response()
+            }
+        }
+
+        let pool = app_state.database.get_pool().await;
+
+        if let Err(res) = fetch_product(pool.deref(), product.as_str(), 4045, "Cannot add the image").await {
+            return res
+        }
+
+        let hash = content_hash(&image);
+
+        match ProductImage::find(pool.deref(), product.as_str(), hash.as_str()).await {
+            Ok(None) => {},
+            Ok(Some(_)) => {
+                return (StatusCode::CONFLICT, Json(ApiError::new(4050, "This image is already in the gallery of the product"))).into_response()
+            }
+            Err(e) => {
+                error!(target: "UploadProductImage", "Cannot fetch the image: {e:#?}");
+                return Json(ApiError::new(4045, "Cannot add the image")).into_response()
+            }
+        }
+
+        if let Err(e) = store(app_state.config.image_dir.as_str(), hash.as_str(), format, &image).await {
+            error!(target: "UploadProductImage", "Cannot write the image '{hash}': {e:#?}");
+            return Json(ApiError::new(4045, "Cannot add the image")).into_response()
+        }
+
+        match ProductImage::add(pool.deref(), product.as_str(), hash.as_str(), format, alt.as_deref()).await {
+            Ok(image) => (StatusCode::CREATED, Json(image)).into_response(),
+            Err(e) => {
+                error!(target: "UploadProductImage", "Cannot add the image: {e:#?}");
+                Json(ApiError::new(4045, "Cannot add the image")).into_response()
+            }
+        }
+    }
+
+    #[derive(Serialize, Deserialize, Clone)]
+    pub(crate) struct ImagePatchForm {
+        /// An empty text removes the alternative text
+        alt: Option<String>
+    }
+
+    /// Route: PATCH /product/:id/images/:hash
+    ///
+    /// Change the alternative text of an image of a product, restricted to the admins
+    pub(crate) async fn edit_product_image(
+        State(app_state): State<AppState>,
+        Path((product, hash)): Path<(String, String)>,
+        _: Admin,
+        Form(form): Form<ImagePatchForm>
+    ) -> impl IntoResponse
+    {
+        let alt = clean_alt(form.alt);
+        if let Err(e) = ProductImage::validate_alt(alt.as_deref()) {
+            return unprocessable(e)
+        }
+
+        let pool = app_state.database.get_pool().await;
+
+        let mut image = match ProductImage::find(pool.deref(), product.as_str(), hash.as_str()).await {
+            Ok(Some(image)) => image,
+            Ok(None) => return image_not_found(),
+            Err(e) => {
+                error!(target: "EditProductImage", "Cannot fetch the image: {e:#?}");
+                return Json(ApiError::new(4051, "Cannot edit the image")).into_response()
+            }
+        };
+
+        match ProductImage::update_alt(pool.deref(), product.as_str(), hash.as_str(), alt.as_deref()).await {
+            Ok(_) => {
+                image.alt = alt;
+                Json(image).into_response()
+            }
+            Err(e) => {
+                error!(target: "EditProductImage", "Cannot edit the image: {e:#?}");
+                Json(ApiError::new(4051, "Cannot edit the image")).into_response()
+            }
+        }
+    }
+
+    /// Route: DELETE /product/:id/images/:hash
+    ///
+    /// Remove an image from the gallery of a product, restricted to the admins
+    ///
+    /// The file is kept on the disk, another product may use the same content
+    pub(crate) async fn delete_product_image(
+        State(app_state): State<AppState>,
+        Path((product, hash)): Path<(String, String)>,
+        _: Admin
+    ) -> impl IntoResponse
+    {
+        let pool = app_state.database.get_pool().await;
+
+        match ProductImage::delete(pool.deref(), product.as_str(), hash.as_str()).await {
+            Ok(true) => Json(json!({"message": "Image deleted", "code": 4104})).into_response(),
+            Ok(false) => image_not_found(),
+            Err(e) => {
+                error!(target: "DeleteProductImage", "Cannot delete the image: {e:#?}");
+                Json(ApiError::new(4052, "Cannot delete the image")).into_response()
+            }
+        }
+    }
+
+    /// Route: PUT /product/:id/images/order
+    ///
+    /// Reorder the gallery of a product, the JSON body is the list of every image hash in the new order
+    pub(crate) async fn reorder_product_images(
+        State(app_state): State<AppState>,
+        Path(product): Path<String>,
+        _: Admin,
+        Json(hashes): Json<Vec<String>>
+    ) -> impl IntoResponse
+    {
+        let pool = app_state.database.get_pool().await;
+
+        if let Err(res) = fetch_product(pool.deref(), product.as_str(), 4053, "Cannot reorder the images").await {
+            return res
+        }
+
+        match ProductImage::reorder(pool.deref(), product.as_str(), hashes.as_slice()).await {
+            Ok(true) => {},
+            Ok(false) => {
+                return unprocessable(ApiError::new(4054, "The list must contain every image of the product exactly once"))
+            }
+            Err(e) => {
+                error!(target: "ReorderProductImages", "Cannot reorder the images: {e:#?}");
+                return Json(ApiError::new(4053, "Cannot reorder the images")).into_response()
+            }
+        }
+
+        match ProductImage::get_of_product(pool.deref(), product.as_str()).await {
+            Ok(images) => Json(images).into_response(),
+            Err(e) => {
+                error!(target: "ReorderProductImages", "Cannot fetch the images: {e:#?}");
+                Json(ApiError::new(4053, "Cannot reorder the images")).into_response()
+            }
+        }
+    }
+
+    /// Route: GET /images/:hash
+    ///
+    /// Get the file of an image, its content never changes for a given hash
+    pub(crate) async fn get_image(
+        State(app_state): State<AppState>,
+        Path(hash): Path<String>
+    ) -> impl IntoResponse
+    {
+        if !is_hash(hash.as_str()) {
+            return image_not_found()
+        }
+
+        let pool = app_state.database.get_pool().await;
+
+        let format = match ProductImage::format_of(pool.deref(), hash.as_str()).await {
+            Ok(Some(format)) => format,
+            Ok(None) => return image_not_found(),
+            Err(e) => {
+                error!(target: "GetImage", "Cannot fetch the image '{hash}': {e:#?}");
+                return Json(ApiError::new(4055, "Cannot get the image")).into_response()
+            }
+        };
+
+        match tokio::fs::read(file_path(app_state.config.image_dir.as_str(), hash.as_str(), format)).await {
+            Ok(bytes) => image_file(format, bytes),
+            Err(e) => {
+                error!(target: "GetImage", "Cannot read the image '{hash}': {e:#?}");
+                Json(ApiError::new(4055, "Cannot get the image")).into_response()
+            }
+        }
+    }
+
+    /// Route: GET /images/:hash/:width
+    ///
+    /// Get the image shrunk to one of the widths listed in its `sizes`, in its own format,
+    /// or in WebP when the width is followed by `.webp`
+    pub(crate) async fn get_resized_image(
+        State(app_state): State<AppState>,
+        Path((hash, size)): Path<(String, String)>
+    ) -> impl IntoResponse
+    {
+        let (width, webp) = match size.strip_suffix(".webp") {
+            Some(width) => (width, true),
+            None => (size.as_str(), false)
+        };
+        let width = match width.parse::<u32>() {
+            Ok(width) if IMAGE_WIDTHS.contains(&width) => width,
+            _ => {
+                return (
+                    StatusCode::NOT_FOUND,
+                    Json(ApiError::new(4056, format!("This size does not exist, the widths are {IMAGE_WIDTHS:?}")))
+                ).into_response()
+            }
+        };
+        if !is_hash(hash.as_str()) {
+            return image_not_found()
+        }
+
+        let pool = app_state.database.get_pool().await;
+
+        let source = match ProductImage::format_of(pool.deref(), hash.as_str()).await {
+            Ok(Some(format)) => format,
+            Ok(None) => return image_not_found(),
+            Err(e) => {
+                error!(target: "GetResizedImage", "Cannot fetch the image '{hash}': {e:#?}");
+                return Json(ApiError::new(4055, "Cannot get the image")).into_response()
+            }
+        };
+        let target = if webp { ImageFormat::Webp } else { source };
+
+        match resized(app_state.config.image_dir.as_str(), hash.as_str(), source, width, target).await {
+            Ok(bytes) => image_file(target, bytes),
+            Err(e) => {
+                error!(target: "GetResizedImage", "Cannot resize the image '{hash}' to {width}: {e:#?}");
+                Json(ApiError::new(4055, "Cannot get the image")).into_response()
+            }
+        }
+    }
+}
@@ -1,39 +1,388 @@
+use chrono::NaiveDateTime;
 use serde::{Deserialize, Serialize};
-use sqlx::MySqlPool;
+use sqlx::{MySql, MySqlPool, QueryBuilder};
 use uuid::Uuid;
 use crate::clients::ClientId;
+use crate::constants::{
+    CHARACTERISTIC_DETAIL_MAX_LENGTH,
+    CHARACTERISTIC_NAME_MAX_LENGTH,
+    PRODUCT_DESCRIPTION_MAX_LENGTH,
+    PRODUCT_NAME_MAX_LENGTH,
+    PRODUCTS_PER_PAGE,
+    REVIEW_MAX_LENGTH,
+    REVIEW_MAX_STARS,
+    REVIEW_MIN_STARS
+};
+use crate::declaration::ApiError;
+use crate::money::Money;
+use crate::pagination::{Cursor, Page, Pagination};
+use crate::product::image::ProductImage;
+use crate::product::moderation::ReviewStatus;
+
+pub(crate) mod image;
+pub(crate) mod moderation;
+pub(crate) mod variant;
 
 pub(crate) type ProductId = String;
 
+/// Order of the products of a listing
+#[derive(Serialize, Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum ProductSort {
+    PriceAsc,
+    PriceDesc,
+    #[default]
+    Name,
+    Newest,
+    /// Best average rating first, the products without review last
+    Rating
+}
+
+impl ProductSort {
+    /// Column the products are sorted by, before their id
+    ///
+    /// The key also gives the cursor, as text, so it must compare equal once read back: the average of the stars is rounded
+    /// for the text of a `DOUBLE` to not lose digits and skip or repeat a product between two pages
+    fn key(&self) -> &'static str {
+        match self {
+            Self::PriceAsc | Self::PriceDesc => "product.price",
+            Self::Name => "product.name",
+            Self::Newest => "product.created_at",
+            Self::Rating => "ROUND(ratings.average_stars, 4)"
+        }
+    }
+
+    fn descending(&self) -> bool {
+        matches!(self, Self::PriceDesc | Self::Newest | Self::Rating)
+    }
+}
+
+/// Query parameters of the product listing, every filter is optional
+#[derive(Serialize, Deserialize, Clone)]
+pub(crate) struct ProductQuery {
+    #[serde(default)]
+    pub page: u64,
+    pub page_size: Option<u64>,
+    /// `next_cursor` of the previous page
+    pub cursor: Option<String>,
+    /// In cents
+    pub min_price: Option<u64>,
+    /// In cents
+    pub max_price: Option<u64>,
+    /// Slug of a category, the products of its sub-categories are included
+    pub category: Option<String>,
+    /// Minimum average of the stars of the reviews
+    pub min_rating: Option<f32>,
+    /// Only the products with a variant in stock when `true`, only the ones out of stock when `false`
+    pub available: Option<bool>,
+    #[serde(default)]
+    pub sort: ProductSort
+}
+
+impl ProductQuery {
+    pub(crate) fn pagination(&self) -> Result<Pagination, ApiError> {
+        Pagination::new(self.page, self.page_size, PRODUCTS_PER_PAGE, self.cursor.as_deref())
+    }
+
+    pub(crate) fn validate(&self) -> Result<(), ApiError> {
+        if let (Some(min), Some(max)) = (self.min_price, self.max_price) {
+            if min > max {
+                return Err(ApiError::new(4027, "The minimum price cannot be greater than the maximum price"))
+            }
+        }
+        if self.min_rating.is_some_and(|r| !(0.0..=5.0).contains(&r)) {
+            return Err(ApiError::new(4028, "The minimum rating must be between 0 and 5"))
+        }
+        Ok(())
+    }
+}
+
+
+/// A product of a listing, with the value of the column it is sorted by
+#[derive(sqlx::FromRow)]
+struct ListedProduct {
+    #[sqlx(flatten)]
+    product: Product,
+    sort_key: Option<String>
+}
 
 #[derive(sqlx::FromRow, Serialize, Deserialize, Clone)]
 pub(crate) struct Product {
     pub id: ProductId,
     pub name: String,
     pub description: Option<String>,
-    pub price: u64,
+    /// Read from the `price` and `currency` columns
+    #[sqlx(flatten)]
+    pub price: Money,
 
-    pub main_image: Option<String>,
-    pub second_image: Option<String>,
-    pub third_image: Option<String>,
-    pub fourth_image: Option<String>,
+    /// The gallery of the product, in display order, read only
+    #[sqlx(skip)]
+    #[serde(default, skip_deserializing)]
+    pub images: Vec<ProductImage>,
 
-    pub size: bool,
-    pub wheel_size: bool
+    /// Sum of the stock of the variants, read only
+    #[serde(default, skip_deserializing)]
+    pub stock: u64,
+    /// Whether one of the variants is in stock, read only
+    #[serde(default, skip_deserializing)]
+    pub available: bool,
+
+    /// Mean of the stars of the reviews, `None` without any review, read only
+    #[serde(default, skip_deserializing)]
+    pub average_stars: Option<f64>,
+    /// Read only
+    #[serde(default, skip_deserializing)]
+    pub review_count: u64
 }
 
+/// Stock of the products, summed over their variants, and their ratings from the approved reviews,
+/// each computed by one grouped query
+const PRODUCT_AGGREGATES: &str = r#"LEFT JOIN (
+    SELECT product, SUM(stock) AS stock FROM product_variant GROUP BY product
+) inventory ON inventory.product = product.id
+LEFT JOIN (
+    SELECT product, CAST(AVG(stars) AS DOUBLE) AS average_stars, COUNT(*) AS review_count
+    FROM reviews WHERE status = 'approved' GROUP BY product
+) ratings ON ratings.product = product.id"#;
+
+/// Columns of a [`Product`], the `product` table must be joined with [`PRODUCT_AGGREGATES`]
+const PRODUCT_COLUMNS: &str = r#"product.*,
+    CAST(COALESCE(inventory.stock, 0) AS UNSIGNED) AS stock,
+    COALESCE(inventory.stock, 0) > 0 AS available,
+    ratings.average_stars,
+    CAST(COALESCE(ratings.review_count, 0) AS UNSIGNED) AS review_count"#;
+
 impl Product {
     async fn get_all(pool: &MySqlPool) -> Result<Vec<Self>, sqlx::Error> {
-        sqlx::query_as::<_, Product>("SELECT * FROM product")
+        let products = sqlx::query_as::<_, Product>(format!("SELECT {PRODUCT_COLUMNS} FROM product {PRODUCT_AGGREGATES}").as_str())
             .fetch_all(pool)
-            .await
+            .await?;
+
+        Self::with_images(pool, products).await
     }
 
     async fn get_product(pool: &MySqlPool, id: &str) -> Result<Self, sqlx::Error> {
-        sqlx::query_as::<_, Product>("SELECT * FROM product WHERE id = ?")
+        Self::find_product(pool, id).await?.ok_or(sqlx::Error::RowNotFound)
+    }
+
+    pub(crate) async fn find_product(pool: &MySqlPool, id: &str) -> Result<Option<Self>, sqlx::Error> {
+        let product = sqlx::query_as::<_, Product>(format!("SELECT {PRODUCT_COLUMNS} FROM product {PRODUCT_AGGREGATES} WHERE id = ?").as_str())
             .bind(id)
+            .fetch_optional(pool)
+            .await?;
+
+        match product {
+            Some(mut product) => {
+                product.images = ProductImage::get_of_product(pool, id).await?;
+                Ok(Some(product))
+            }
+            None => Ok(None)
+        }
+    }
+
+    /// Fill the galleries of the products, with one query for all of them
+    async fn with_images(pool: &MySqlPool, mut products: Vec<Self>) -> Result<Vec<Self>, sqlx::Error> {
+        let ids: Vec<ProductId> = products.iter().map(|p| p.id.clone()).collect();
+        let mut galleries = ProductImage::get_of_products(pool, ids.as_slice()).await?;
+
+        for product in products.iter_mut() {
+            product.images = galleries.remove(&product.id).unwrap_or_default();
+        }
+
+        Ok(products)
+    }
+
+    /// Start the listing query: the products matching the filters, with their average rating and their stock
+    fn listing<'a>(query: &ProductQuery, select: &str) -> QueryBuilder<'a, MySql> {
+        let mut builder: QueryBuilder<MySql> = QueryBuilder::new("");
+
+        if let Some(category) = &query.category {
+            builder.push(
+                r#"WITH RECURSIVE subcategories AS (
+                    SELECT id FROM category WHERE slug = "#
+            );
+            builder.push_bind(category.clone());
+            builder.push(
+                r#" UNION ALL
+                    SELECT category.id FROM category JOIN subcategories ON category.parent = subcategories.id
+                ) "#
+            );
+        }
+
+        builder.push("SELECT ").push(select);
+        builder.push(" FROM product ").push(PRODUCT_AGGREGATES).push(" WHERE TRUE");
+
+        if query.category.is_some() {
+            builder.push(" AND product.id IN (SELECT product FROM product_category WHERE category IN (SELECT id FROM subcategories))");
+        }
+        if let Some(min_price) = query.min_price {
+            builder.push(" AND product.price >= ").push_bind(min_price);
+        }
+        if let Some(max_price) = query.max_price {
+            builder.push(" AND product.price <= ").push_bind(max_price);
+        }
+        if let Some(min_rating) = query.min_rating {
+            builder.push(" AND ratings.average_stars >= ").push_bind(min_rating);
+        }
+        match query.available {
+            Some(true) => { builder.push(" AND inventory.stock > 0"); },
+            Some(false) => { builder.push(" AND COALESCE(inventory.stock, 0) = 0"); },
+            None => {}
+        }
+
+        builder
+    }
+
+    /// Fetch a page of the products matching the filters, in the requested order
+    pub(crate) async fn get_page(pool: &MySqlPool, query: &ProductQuery, pagination: Pagination) -> Result<Page<Self>, sqlx::Error> {
+        let total: i64 = Self::listing(query, "COUNT(*)")
+            .build_query_scalar()
             .fetch_one(pool)
+            .await?;
+
+        let sort = query.sort;
+        let key = sort.key();
+        let mut builder = Self::listing(query, format!("{PRODUCT_COLUMNS}, CAST({key} AS CHAR) AS sort_key").as_str());
+
+        // keyset pagination: only the products after the last one of the previous page
+        if let Some(cursor) = &pagination.cursor {
+            match &cursor.key {
+                Some(value) => {
+                    let after = if sort.descending() { "<" } else { ">" };
+                    builder.push(format!(" AND ({key} {after} ")).push_bind(value.clone());
+                    builder.push(format!(" OR ({key} = ")).push_bind(value.clone());
+                    builder.push(" AND product.id > ").push_bind(cursor.id.clone()).push(")");
+                    // the products without sort key come last
+                    builder.push(format!(" OR {key} IS NULL)"));
+                }
+                None => {
+                    builder.push(format!(" AND {key} IS NULL AND product.id > ")).push_bind(cursor.id.clone());
+                }
+            }
+        }
+
+        let direction = if sort.descending() { "DESC" } else { "ASC" };
+        builder.push(format!(" ORDER BY {key} IS NULL, {key} {direction}, product.id"));
+        builder.push(" LIMIT ").push_bind(pagination.page_size + 1);
+        builder.push(" OFFSET ").push_bind(pagination.offset());
+
+        let rows = builder.build_query_as::<ListedProduct>()
+            .fetch_all(pool)
+            .await?;
+
+        let page = pagination.into_page(rows, total as u64, |row| Cursor { key: row.sort_key.clone(), id: row.product.id.clone() });
+
+        let products = Self::with_images(pool, page.items.into_iter().map(|row| row.product).collect()).await?;
+
+        Ok(Page {
+            items: products,
+            page: page.page,
+            page_size: page.page_size,
+            total: page.total,
+            next_cursor: page.next_cursor
+        })
+    }
+
+    /// Fetch a page of the products matching the words of the query, the most relevant first
+    ///
+    /// The name and description of the products weigh twice as much as the details of their characteristics
+    async fn search(pool: &MySqlPool, query: &str, pagination: Pagination) -> Result<Page<Self>, sqlx::Error> {
+        const MATCHING: &str = r#"LEFT JOIN (
+                SELECT product, SUM(MATCH (detail) AGAINST (?)) AS relevance
+                FROM product_characteristic
+                WHERE MATCH (detail) AGAINST (?)
+                GROUP BY product
+            ) characteristics ON characteristics.product = product.id
+            WHERE MATCH (product.name, product.description) AGAINST (?) OR characteristics.relevance > 0"#;
+
+        let total: i64 = sqlx::query_scalar(format!("SELECT COUNT(*) FROM product {MATCHING}").as_str())
+            .bind(query)
+            .bind(query)
+            .bind(query)
+            .fetch_one(pool)
+            .await?;
+
+        let products = sqlx::query_as::<_, Product>(
+            format!(
+                r#"SELECT {PRODUCT_COLUMNS},
+                    MATCH (product.name, product.description) AGAINST (?) * 2 + COALESCE(characteristics.relevance, 0) AS score
+                FROM product {PRODUCT_AGGREGATES}
+                {MATCHING}
+                ORDER BY score DESC, product.id
+                LIMIT ? OFFSET ?"#
+            ).as_str()
+        )
+            .bind(query)
+            .bind(query)
+            .bind(query)
+            .bind(query)
+            .bind(pagination.page_size)
+            .bind(pagination.offset())
+            .fetch_all(pool)
+            .await?;
+
+        let products = Self::with_images(pool, products).await?;
+
+        Ok(pagination.into_offset_page(products, total as u64))
+    }
+
+    /// Check the product against the constraints of the `product` table
+    fn validate(&self) -> Result<(), ApiError> {
+        let name = self.name.trim();
+        if name.is_empty() || name.chars().count() > PRODUCT_NAME_MAX_LENGTH {
+            return Err(ApiError::new(4007, format!("The name must contain between 1 and {PRODUCT_NAME_MAX_LENGTH} characters")))
+        }
+        if self.description.as_ref().is_some_and(|d| d.chars().count() > PRODUCT_DESCRIPTION_MAX_LENGTH) {
+            return Err(ApiError::new(4008, format!("The description must contain at most {PRODUCT_DESCRIPTION_MAX_LENGTH} characters")))
+        }
+        if self.price.cents == 0 {
+            return Err(ApiError::new(4009, "The price must be strictly positive"))
+        }
+
+        Ok(())
+    }
+
+    /// Create the product with its default variant, which has no option and no stock
+    async fn create(pool: &MySqlPool, product: &Self) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        sqlx::query("INSERT INTO product (id, name, description, price, currency) VALUE (?, ?, ?, ?, ?)")
+            .bind(&product.id)
+            .bind(&product.name)
+            .bind(&product.description)
+            .bind(product.price.cents)
+            .bind(product.price.currency)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query("INSERT INTO product_variant (id, product) VALUE (?, ?)")
+            .bind(Uuid::new_v4().to_string())
+            .bind(&product.id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await
+    }
+
+    async fn update(pool: &MySqlPool, product: &Self) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE product SET name = ?, description = ?, price = ?, currency = ? WHERE id = ?")
+            .bind(&product.name)
+            .bind(&product.description)
+            .bind(product.price.cents)
+            .bind(product.price.currency)
+            .bind(&product.id)
+            .execute(pool)
             .await
+            .map(|_| ())
+    }
+
+    /// Delete the product, its characteristics, variants, images, reviews and cart articles are deleted by the cascade
+    async fn delete(pool: &MySqlPool, id: &str) -> Result<bool, sqlx::Error> {
+        sqlx::query("DELETE FROM product WHERE id = ?")
+            .bind(id)
+            .execute(pool)
+            .await
+            .map(|res| res.rows_affected() > 0)
     }
 
     // async fn get_characteristics(pool: &MySqlPool, id: &str) -> Result<Vec<ProductCharacteristic>, sqlx::Error> {
@@ -46,45 +395,272 @@ pub(crate) struct ProductCharacteristic {
     pub(crate) product: String,
     pub(crate) name: String,
     pub(crate) detail: String,
+    /// The characteristics of a product are displayed by ascending position
+    pub(crate) position: u32,
 }
 
 impl ProductCharacteristic {
     async fn get_product_characteristics(pool: &MySqlPool, product_id: &str) -> Result<Vec<Self>, sqlx::Error> {
-        sqlx::query_as::<_, ProductCharacteristic>("SELECT * FROM product_characteristic WHERE product = ?")
+        sqlx::query_as::<_, ProductCharacteristic>("SELECT * FROM product_characteristic WHERE product = ? ORDER BY position, name")
             .bind(product_id)
             .fetch_all(pool)
             .await
     }
+
+    async fn find(pool: &MySqlPool, product_id: &str, name: &str) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, ProductCharacteristic>("SELECT * FROM product_characteristic WHERE product = ? AND name = ?")
+            .bind(product_id)
+            .bind(name)
+            .fetch_optional(pool)
+            .await
+    }
+
+    /// Check the characteristic against the constraints of the `product_characteristic` table
+    fn validate(name: &str, detail: &str) -> Result<(), ApiError> {
+        if name.is_empty() || name.chars().count() > CHARACTERISTIC_NAME_MAX_LENGTH {
+            return Err(ApiError::new(4015, format!("The name must contain between 1 and {CHARACTERISTIC_NAME_MAX_LENGTH} characters")))
+        }
+        if detail.chars().count() > CHARACTERISTIC_DETAIL_MAX_LENGTH {
+            return Err(ApiError::new(4016, format!("The detail must contain at most {CHARACTERISTIC_DETAIL_MAX_LENGTH} characters")))
+        }
+        Ok(())
+    }
+
+    /// Add the characteristic, at the end of the list if no position is given
+    async fn add(pool: &MySqlPool, product_id: &str, name: &str, detail: &str, position: Option<u32>) -> Result<Self, sqlx::Error> {
+        let position = match position {
+            Some(position) => position,
+            None => {
+                let last: Option<u32> = sqlx::query_scalar("SELECT MAX(position) FROM product_characteristic WHERE product = ?")
+                    .bind(product_id)
+                    .fetch_one(pool)
+                    .await?;
+                last.map_or(0, |p| p + 1)
+            }
+        };
+
+        sqlx::query("INSERT INTO product_characteristic (product, name, detail, position) VALUE (?, ?, ?, ?)")
+            .bind(product_id)
+            .bind(name)
+            .bind(detail)
+            .bind(position)
+            .execute(pool)
+            .await?;
+
+        Ok(Self { product: product_id.to_string(), name: name.to_string(), detail: detail.to_string(), position })
+    }
+
+    /// Save the characteristic, `old_name` is its name before a rename
+    async fn update(pool: &MySqlPool, old_name: &str, characteristic: &Self) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE product_characteristic SET name = ?, detail = ?, position = ? WHERE product = ? AND name = ?")
+            .bind(&characteristic.name)
+            .bind(&characteristic.detail)
+            .bind(characteristic.position)
+            .bind(&characteristic.product)
+            .bind(old_name)
+            .execute(pool)
+            .await
+            .map(|_| ())
+    }
+
+    async fn delete(pool: &MySqlPool, product_id: &str, name: &str) -> Result<bool, sqlx::Error> {
+        sqlx::query("DELETE FROM product_characteristic WHERE product = ? AND name = ?")
+            .bind(product_id)
+            .bind(name)
+            .execute(pool)
+            .await
+            .map(|res| res.rows_affected() > 0)
+    }
+
+    /// Give each characteristic its index in `names` as position, in one transaction
+    ///
+    /// Return `false`, without changing anything, if `names` is not exactly the list of the characteristics,
+    /// the names are compared without case like the primary key of the table
+    async fn reorder(pool: &MySqlPool, product_id: &str, names: &[String]) -> Result<bool, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        let mut existing: Vec<String> = sqlx::query_scalar("SELECT name FROM product_characteristic WHERE product = ? FOR UPDATE")
+            .bind(product_id)
+            .fetch_all(&mut *tx)
+            .await?;
+
+        let mut wanted: Vec<String> = names.iter().map(|n| n.to_lowercase()).collect();
+        existing.iter_mut().for_each(|n| *n = n.to_lowercase());
+        existing.sort();
+        wanted.sort();
+        if existing != wanted {
+            return Ok(false)
+        }
+
+        for (position, name) in names.iter().enumerate() {
+            sqlx::query("UPDATE product_characteristic SET position = ? WHERE product = ? AND name = ?")
+                .bind(position as u32)
+                .bind(product_id)
+                .bind(name)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await.map(|_| true)
+    }
+
+    /// Replace every characteristic of the product by the given ones, in one transaction
+    async fn replace_all(pool: &MySqlPool, product_id: &str, characteristics: &[Self]) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        sqlx::query("DELETE FROM product_characteristic WHERE product = ?")
+            .bind(product_id)
+            .execute(&mut *tx)
+            .await?;
+
+        for characteristic in characteristics {
+            sqlx::query("INSERT INTO product_characteristic (product, name, detail, position) VALUE (?, ?, ?, ?)")
+                .bind(product_id)
+                .bind(&characteristic.name)
+                .bind(&characteristic.detail)
+                .bind(characteristic.position)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await
+    }
 }
 
 
 #[derive(sqlx::FromRow, Serialize, Deserialize, Clone)]
-struct Review {
+pub(crate) struct Review {
     pub id: String,
     pub product: ProductId,
-    pub user: ClientId,
+    /// `None` once the author has deleted its account
+    pub user: Option<ClientId>,
     pub review: String,
     pub stars: u8,
-    pub username: Option<String>
+    pub username: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub status: ReviewStatus,
+    /// Given by the moderator who rejected the review
+    pub rejection_reason: Option<String>
 }
 
-const GET_ALL_REVIEWS_QUERY: &str = r#"SELECT
+/// Number of reviews of a product giving a star value
+#[derive(Serialize, Deserialize, Clone)]
+pub(crate) struct StarCount {
+    pub stars: u8,
+    pub count: u64,
+    /// Share of the reviews of the product, between 0 and 100
+    pub percentage: f64
+}
+
+/// Distribution of the stars given to a product
+#[derive(Serialize, Deserialize, Clone)]
+pub(crate) struct ReviewSummary {
+    pub total: u64,
+    /// Mean of the stars, `None` without any review
+    pub average: Option<f64>,
+    /// One entry per star value, from 5 down to 1, including the values nobody gave
+    pub histogram: Vec<StarCount>
+}
+
+/// The reviews with the name of their author, to complete with the conditions
+const REVIEWS_QUERY: &str = r#"SELECT
     reviews.id AS id,
     reviews.product AS product,
     reviews.user AS user,
     reviews.review AS review,
     reviews.stars AS stars,
-    c.username AS username
+    c.username AS username,
+    reviews.created_at AS created_at,
+    reviews.status AS status,
+    reviews.rejection_reason AS rejection_reason
 FROM
     reviews
-RIGHT JOIN clients c on reviews.user = c.id
-WHERE product = ?;"#;
+LEFT JOIN clients c on reviews.user = c.id"#;
 
 impl Review {
-    async fn get_reviews_of_product(pool: &MySqlPool, product: &ProductId) -> Result<Vec<Self>, sqlx::Error> {
-        sqlx::query_as::<_, Self>(GET_ALL_REVIEWS_QUERY)
+    /// Fetch a page of the approved reviews of the product, the most recent first
+    async fn get_reviews_of_product(pool: &MySqlPool, product: &str, pagination: Pagination) -> Result<Page<Self>, sqlx::Error> {
+        let total: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM reviews WHERE product = ? AND status = 'approved'")
+            .bind(product)
+            .fetch_one(pool)
+            .await?;
+
+        let mut builder: QueryBuilder<MySql> = QueryBuilder::new(REVIEWS_QUERY);
+        builder.push(" WHERE reviews.status = 'approved' AND reviews.product = ").push_bind(product);
+
+        // keyset pagination: only the reviews older than the last one of the previous page
+        if let Some(Cursor { key: Some(created_at), id }) = &pagination.cursor {
+            builder.push(" AND (reviews.created_at < ").push_bind(created_at.clone());
+            builder.push(" OR (reviews.created_at = ").push_bind(created_at.clone());
+            builder.push(" AND reviews.id > ").push_bind(id.clone()).push("))");
+        }
+
+        builder.push(" ORDER BY reviews.created_at DESC, reviews.id");
+        builder.push(" LIMIT ").push_bind(pagination.page_size + 1);
+        builder.push(" OFFSET ").push_bind(pagination.offset());
+
+        let reviews = builder.build_query_as::<Self>()
+            .fetch_all(pool)
+            .await?;
+
+        Ok(pagination.into_page(reviews, total as u64, |review| Cursor {
+            key: Some(review.created_at.format("%Y-%m-%d %H:%M:%S").to_string()),
+            id: review.id.clone()
+        }))
+    }
+
+    /// Count the approved reviews of the product per star value, with one grouped query
+    async fn summary(pool: &MySqlPool, product: &str) -> Result<ReviewSummary, sqlx::Error> {
+        let counts: Vec<(u8, i64)> = sqlx::query_as("SELECT stars, COUNT(*) FROM reviews WHERE product = ? AND status = 'approved' GROUP BY stars")
             .bind(product)
             .fetch_all(pool)
+            .await?;
+
+        let total: u64 = counts.iter().map(|(_, count)| *count as u64).sum();
+        let sum: u64 = counts.iter().map(|(stars, count)| *stars as u64 * *count as u64).sum();
+
+        let histogram = (REVIEW_MIN_STARS..=REVIEW_MAX_STARS).rev()
+            .map(|stars| {
+                let count = counts.iter().find(|(s, _)| *s == stars).map_or(0, |(_, count)| *count as u64);
+                StarCount {
+                    stars,
+                    count,
+                    percentage: if total == 0 { 0.0 } else { count as f64 * 100.0 / total as f64 }
+                }
+            })
+            .collect();
+
+        Ok(ReviewSummary {
+            total,
+            average: (total > 0).then(|| sum as f64 / total as f64),
+            histogram
+        })
+    }
+
+    async fn find(pool: &MySqlPool, product: &str, id: &str) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>(format!("{REVIEWS_QUERY} WHERE reviews.id = ? AND reviews.product = ?").as_str())
+            .bind(id)
+            .bind(product)
+            .fetch_optional(pool)
+            .await
+    }
+
+    /// Check the review against the constraints of the `reviews` table
+    fn validate(review: &str, stars: u8) -> Result<(), ApiError> {
+        if !(REVIEW_MIN_STARS..=REVIEW_MAX_STARS).contains(&stars) {
+            return Err(ApiError::new(4062, format!("The stars must be between {REVIEW_MIN_STARS} and {REVIEW_MAX_STARS}")))
+        }
+        if review.chars().count() > REVIEW_MAX_LENGTH {
+            return Err(ApiError::new(4063, format!("The review must contain at most {REVIEW_MAX_LENGTH} characters")))
+        }
+        Ok(())
+    }
+
+    /// Fetch every review of the user whatever its status, the most recent first
+    pub(crate) async fn get_reviews_of_user(pool: &MySqlPool, user: &str) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>(format!("{REVIEWS_QUERY} WHERE reviews.user = ? ORDER BY reviews.created_at DESC").as_str())
+            .bind(user)
+            .fetch_all(pool)
             .await
     }
 
@@ -93,20 +669,42 @@ impl Review {
         product: ProductId,
         user: ClientId,
         review: String,
-        stars: u8
+        stars: u8,
+        status: ReviewStatus
     ) -> Result<Uuid, sqlx::Error>
     {
         let id= Uuid::new_v4();
-        sqlx::query("INSERT INTO reviews (id, product, user, review, stars) VALUE (?, ?, ?, ?, ?);")
+        sqlx::query("INSERT INTO reviews (id, product, user, review, stars, status) VALUE (?, ?, ?, ?, ?, ?);")
             .bind(id.to_string())
             .bind(product)
             .bind(user)
             .bind(review)
             .bind(stars)
+            .bind(status)
             .execute(pool)
             .await
             .map(|_| id)
     }
+
+    /// Change the text and the stars, the review goes through the moderation again
+    async fn update(pool: &MySqlPool, id: &str, review: &str, stars: u8, status: ReviewStatus) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE reviews SET review = ?, stars = ?, status = ?, rejection_reason = NULL WHERE id = ?")
+            .bind(review)
+            .bind(stars)
+            .bind(status)
+            .bind(id)
+            .execute(pool)
+            .await
+            .map(|_| ())
+    }
+
+    async fn delete(pool: &MySqlPool, id: &str) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM reviews WHERE id = ?")
+            .bind(id)
+            .execute(pool)
+            .await
+            .map(|_| ())
+    }
 }
 
 
@@ -116,13 +714,20 @@ pub(crate) mod routes {
     use std::ops::Deref;
     use axum::extract::{Path, Query, State};
     use axum::{Form, Json};
-    use axum::response::IntoResponse;
+    use axum::http::StatusCode;
+    use axum::response::{IntoResponse, Response};
     use serde::{Deserialize, Serialize};
     use serde_json::json;
     use tracing::error;
+    use uuid::Uuid;
     use crate::{AppState, constants};
-    use crate::declaration::ApiError;
-    use crate::product::{Product, ProductCharacteristic, Review};
+    use crate::declaration::{ApiError, double_option, is_unique_violation, unprocessable};
+    use crate::clients::Client;
+    use crate::clients::role::Admin;
+    use crate::money::{Currency, Money};
+    use crate::pagination::Pagination;
+    use crate::product::{Product, ProductCharacteristic, ProductQuery, Review};
+    use crate::product::moderation::ReviewStatus;
 
     pub(crate) async fn get_all_products(
         State(app_state): State<AppState>
@@ -159,30 +764,25 @@ pub(crate) mod routes {
         }
     }
 
-    #[derive(Serialize, Deserialize)]
-    pub(crate) struct ProductPageForm {
-        page: u64
-    }
-
+    /// Route: GET /product/page
+    ///
+    /// Get a page of the products, filtered and sorted by the query parameters
     pub(crate) async fn get_product_page(
         State(app_state): State<AppState>,
-        Query(params): Query<ProductPageForm>
+        Query(query): Query<ProductQuery>
     ) -> impl IntoResponse
     {
-        let pool = app_state.database.get_pool().await;
-
-        let start = if params.page > 0 {
-            params.page * constants::PRODUCTS_PER_PAGE
-        } else {
-            0
+        if let Err(e) = query.validate() {
+            return unprocessable(e)
+        }
+        let pagination = match query.pagination() {
+            Ok(pagination) => pagination,
+            Err(e) => return unprocessable(e)
         };
 
-        let query = format!(
-            "SELECT * FROM product ORDER BY id LIMIT {start}, {end};",
-            end = start + constants::PRODUCTS_PER_PAGE
-        );
+        let pool = app_state.database.get_pool().await;
 
-        match sqlx::query_as::<_, Product>(query.as_str()).fetch_all(pool.deref()).await {
+        match Product::get_page(pool.deref(), &query, pagination).await {
             Ok(products) => {
                 Json(products).into_response()
             }
@@ -193,6 +793,46 @@ pub(crate) mod routes {
         }
     }
 
+    #[derive(Serialize, Deserialize, Clone)]
+    pub(crate) struct ProductSearchForm {
+        q: String,
+        #[serde(default)]
+        page: u64,
+        page_size: Option<u64>
+    }
+
+    /// Route: GET /product/search?q=
+    ///
+    /// Search the products by their name, description and characteristics, ignoring the case and the accents
+    pub(crate) async fn search_products(
+        State(app_state): State<AppState>,
+        Query(params): Query<ProductSearchForm>
+    ) -> impl IntoResponse
+    {
+        let query = params.q.trim();
+        if query.is_empty() || query.chars().count() > constants::SEARCH_QUERY_MAX_LENGTH {
+            return unprocessable(ApiError::new(
+                4025,
+                format!("The search must contain between 1 and {} characters", constants::SEARCH_QUERY_MAX_LENGTH)
+            ))
+        }
+
+        let pagination = match Pagination::new(params.page, params.page_size, constants::PRODUCTS_PER_PAGE, None) {
+            Ok(pagination) => pagination,
+            Err(e) => return unprocessable(e)
+        };
+
+        let pool = app_state.database.get_pool().await;
+
+        match Product::search(pool.deref(), query, pagination).await {
+            Ok(products) => Json(products).into_response(),
+            Err(e) => {
+                error!(target: "SearchProducts", "Cannot search the products: {e:#?}");
+                Json(ApiError::new(4026, "Cannot search the products")).into_response()
+            }
+        }
+    }
+
     pub(crate) async fn get_product_characteristics(
         State(app_state): State<AppState>,
         Path(product): Path<String>
@@ -211,14 +851,33 @@ pub(crate) mod routes {
         }
     }
 
+    #[derive(Serialize, Deserialize, Clone)]
+    pub(crate) struct ReviewPageForm {
+        #[serde(default)]
+        page: u64,
+        page_size: Option<u64>,
+        cursor: Option<String>
+    }
+
+    /// Route: GET /product/:id/reviews
+    ///
+    /// Get a page of the reviews of a product, the most recent first
     pub(crate) async fn get_product_reviews(
         State(app_state): State<AppState>,
-        Path(product): Path<String>
+        Path(product): Path<String>,
+        Query(params): Query<ReviewPageForm>
     ) -> impl IntoResponse
     {
+        let pagination = match Pagination::new(params.page, params.page_size, constants::REVIEWS_PER_PAGE, params.cursor.as_deref())
+            .and_then(Pagination::require_cursor_key)
+        {
+            Ok(pagination) => pagination,
+            Err(e) => return unprocessable(e)
+        };
+
         let pool = app_state.database.get_pool().await;
 
-        match Review::get_reviews_of_product(pool.deref(), &product).await {
+        match Review::get_reviews_of_product(pool.deref(), product.as_str(), pagination).await {
             Ok(reviews) => {
                 Json(reviews).into_response()
             }
@@ -229,22 +888,65 @@ pub(crate) mod routes {
         }
     }
 
+    /// Route: GET /product/:id/reviews/summary
+    ///
+    /// Get the number of reviews of a product per star value, their mean and their total
+    pub(crate) async fn get_product_review_summary(
+        State(app_state): State<AppState>,
+        Path(product): Path<String>
+    ) -> impl IntoResponse
+    {
+        let pool = app_state.database.get_pool().await;
+
+        match Product::find_product(pool.deref(), product.as_str()).await {
+            Ok(Some(_)) => {},
+            Ok(None) => return product_not_found(),
+            Err(e) => {
+                error!(target: "GetProductReviewSummary", "Cannot fetch the product '{product}': {e:#?}");
+                return Json(ApiError::new(4057, "Cannot get the review summary")).into_response()
+            }
+        }
+
+        match Review::summary(pool.deref(), product.as_str()).await {
+            Ok(summary) => Json(summary).into_response(),
+            Err(e) => {
+                error!(target: "GetProductReviewSummary", "Cannot count the reviews of the product '{product}': {e:#?}");
+                Json(ApiError::new(4057, "Cannot get the review summary")).into_response()
+            }
+        }
+    }
+
     #[derive(Serialize, Deserialize)]
     pub(crate) struct ReviewPost {
         review: String,
+        /// From 1 to 5
         stars: u8
     }
 
+    fn review_not_found() -> Response {
+        (StatusCode::NOT_FOUND, Json(ApiError::new(4059, "This review does not exist"))).into_response()
+    }
+
+    /// Route: POST /product/:id/reviews
+    ///
+    /// Review a product, a client can only review a product once
     pub(crate) async fn new_review(
         State(app_state): State<AppState>,
         Path(product): Path<String>,
+        client: Client,
         Form(review): Form<ReviewPost>
     ) -> impl IntoResponse
     {
+        if let Err(e) = Review::validate(review.review.as_str(), review.stars) {
+            return unprocessable(e)
+        }
+
         let pool = app_state.database.get_pool().await;
 
-        match Review::post(pool.deref(), product, constants::TEST_USER_ID.to_string(), review.review, review.stars).await {
-            Ok(id) => {
+        let status = app_state.config.moderation.initial_status(review.review.as_str());
+
+        match Review::post(pool.deref(), product, client.id, review.review, review.stars, status).await {
+            Ok(id) if status == ReviewStatus::Approved => {
                 Json(
                     json!({
                         "id": id.to_string(),
@@ -253,10 +955,504 @@ pub(crate) mod routes {
                     })
                 ).into_response()
             }
+            Ok(id) => {
+                Json(
+                    json!({
+                        "id": id.to_string(),
+                        "code": 4105,
+                        "message": "Review posted, it will be published once approved by a moderator"
+                    })
+                ).into_response()
+            }
+            // the `UNIQUE (user, product)` constraint of the `reviews` table
+            Err(sqlx::Error::Database(e)) if e.is_unique_violation() => {
+                (StatusCode::CONFLICT, Json(ApiError::new(4064, "You have already reviewed this product"))).into_response()
+            }
             Err(e) => {
                 error!(target: "NewReview", "Cannot post the review: {e:#?}");
                 Json(ApiError::new(4006, "Cannot post the review")).into_response()
             }
         }
     }
+
+    /// Fetch the review of the route if the client wrote it, or build the response to return
+    async fn fetch_own_review(pool: &sqlx::MySqlPool, product: &str, id: &str, client: &Client, error_code: u16, message: &str) -> Result<Review, Response> {
+        match Review::find(pool, product, id).await {
+            Ok(Some(review)) if review.user.as_deref() == Some(client.id.as_str()) => Ok(review),
+            Ok(Some(_)) => Err((StatusCode::FORBIDDEN, Json(ApiError::new(4065, "Only the author can change this review"))).into_response()),
+            Ok(None) => Err(review_not_found()),
+            Err(e) => {
+                error!(target: "FetchOwnReview", "Cannot fetch the review '{id}': {e:#?}");
+                Err(Json(ApiError::new(error_code, message)).into_response())
+            }
+        }
+    }
+
+    /// Route: PUT /product/:id/reviews/:review_id
+    ///
+    /// Rewrite a review, restricted to its author
+    ///
+    /// The new text goes through the moderation again
+    pub(crate) async fn edit_review(
+        State(app_state): State<AppState>,
+        Path((product, id)): Path<(String, String)>,
+        client: Client,
+        Form(form): Form<ReviewPost>
+    ) -> impl IntoResponse
+    {
+        if let Err(e) = Review::validate(form.review.as_str(), form.stars) {
+            return unprocessable(e)
+        }
+
+        let pool = app_state.database.get_pool().await;
+
+        let mut review = match fetch_own_review(pool.deref(), product.as_str(), id.as_str(), &client, 4066, "Cannot edit the review").await {
+            Ok(review) => review,
+            Err(res) => return res
+        };
+
+        let status = app_state.config.moderation.initial_status(form.review.as_str());
+
+        match Review::update(pool.deref(), id.as_str(), form.review.as_str(), form.stars, status).await {
+            Ok(_) => {
+                review.review = form.review;
+                review.stars = form.stars;
+                review.status = status;
+                review.rejection_reason = None;
+                Json(review).into_response()
+            }
+            Err(e) => {
+                error!(target: "EditReview", "Cannot edit the review '{id}': {e:#?}");
+                Json(ApiError::new(4066, "Cannot edit the review")).into_response()
+            }
+        }
+    }
+
+    /// Route: DELETE /product/:id/reviews/:review_id
+    ///
+    /// Delete a review, restricted to its author
+    pub(crate) async fn delete_review(
+        State(app_state): State<AppState>,
+        Path((product, id)): Path<(String, String)>,
+        client: Client
+    ) -> impl IntoResponse
+    {
+        let pool = app_state.database.get_pool().await;
+
+        if let Err(res) = fetch_own_review(pool.deref(), product.as_str(), id.as_str(), &client, 4067, "Cannot delete the review").await {
+            return res
+        }
+
+        match Review::delete(pool.deref(), id.as_str()).await {
+            Ok(_) => Json(json!({"message": "Review deleted", "code": 4108})).into_response(),
+            Err(e) => {
+                error!(target: "DeleteReview", "Cannot delete the review '{id}': {e:#?}");
+                Json(ApiError::new(4067, "Cannot delete the review")).into_response()
+            }
+        }
+    }
+
+    #[derive(Serialize, Deserialize, Clone)]
+    pub(crate) struct ProductForm {
+        name: String,
+        description: Option<String>,
+        /// In cents
+        price: u64,
+        currency: Option<Currency>
+    }
+
+    impl ProductForm {
+        fn into_product(self, id: String) -> Product {
+            Product {
+                id,
+                name: self.name.trim().to_string(),
+                description: self.description,
+                price: Money::new(self.price, self.currency.unwrap_or_default()),
+                images: Vec::new(),
+                stock: 0,
+                available: false,
+                average_stars: None,
+                review_count: 0
+            }
+        }
+    }
+
+    /// Every field is optional, only the given ones are changed, a `null` description removes it
+    #[derive(Serialize, Deserialize, Clone)]
+    pub(crate) struct ProductPatchForm {
+        name: Option<String>,
+        #[serde(default, deserialize_with = "double_option")]
+        description: Option<Option<String>>,
+        /// In cents
+        price: Option<u64>,
+        currency: Option<Currency>
+    }
+
+    impl ProductPatchForm {
+        fn apply(self, product: Product) -> Product {
+            Product {
+                id: product.id,
+                name: self.name.map(|n| n.trim().to_string()).unwrap_or(product.name),
+                description: self.description.unwrap_or(product.description),
+                price: Money::new(
+                    self.price.unwrap_or(product.price.cents),
+                    self.currency.unwrap_or(product.price.currency)
+                ),
+                ..product
+            }
+        }
+    }
+
+    fn product_not_found() -> Response {
+        (StatusCode::NOT_FOUND, Json(ApiError::new(4011, "This product does not exist"))).into_response()
+    }
+
+    /// Route: POST /product
+    ///
+    /// Create a new product, restricted to the admins
+    pub(crate) async fn create_product(
+        State(app_state): State<AppState>,
+        _: Admin,
+        Form(form): Form<ProductForm>
+    ) -> impl IntoResponse
+    {
+        let product = form.into_product(Uuid::new_v4().to_string());
+        if let Err(e) = product.validate() {
+            return unprocessable(e)
+        }
+
+        let pool = app_state.database.get_pool().await;
+
+        match Product::create(pool.deref(), &product).await {
+            Ok(_) => (StatusCode::CREATED, Json(product)).into_response(),
+            Err(e) => {
+                error!(target: "CreateProduct", "Cannot create the product: {e:#?}");
+                Json(ApiError::new(4012, "Cannot create the product")).into_response()
+            }
+        }
+    }
+
+    /// Route: PUT /product/:id
+    ///
+    /// Replace every field of a product, restricted to the admins
+    pub(crate) async fn replace_product(
+        State(app_state): State<AppState>,
+        Path(id): Path<String>,
+        _: Admin,
+        Form(form): Form<ProductForm>
+    ) -> impl IntoResponse
+    {
+        let product = form.into_product(id);
+        if let Err(e) = product.validate() {
+            return unprocessable(e)
+        }
+
+        let pool = app_state.database.get_pool().await;
+
+        match Product::find_product(pool.deref(), product.id.as_str()).await {
+            Ok(Some(_)) => {},
+            Ok(None) => return product_not_found(),
+            Err(e) => {
+                error!(target: "ReplaceProduct", "Cannot fetch the product '{}': {e:#?}", product.id);
+                return Json(ApiError::new(4013, "Cannot update the product")).into_response()
+            }
+        }
+
+        match Product::update(pool.deref(), &product).await {
+            Ok(_) => updated_product(pool.deref(), product.id.as_str()).await,
+            Err(e) => {
+                error!(target: "ReplaceProduct", "Cannot update the product '{}': {e:#?}", product.id);
+                Json(ApiError::new(4013, "Cannot update the product")).into_response()
+            }
+        }
+    }
+
+    /// Route: PATCH /product/:id
+    ///
+    /// Change some fields of a product from a JSON body, restricted to the admins
+    pub(crate) async fn edit_product(
+        State(app_state): State<AppState>,
+        Path(id): Path<String>,
+        _: Admin,
+        Json(form): Json<ProductPatchForm>
+    ) -> impl IntoResponse
+    {
+        let pool = app_state.database.get_pool().await;
+
+        let product = match Product::find_product(pool.deref(), id.as_str()).await {
+            Ok(Some(product)) => form.apply(product),
+            Ok(None) => return product_not_found(),
+            Err(e) => {
+                error!(target: "EditProduct", "Cannot fetch the product '{id}': {e:#?}");
+                return Json(ApiError::new(4013, "Cannot update the product")).into_response()
+            }
+        };
+
+        if let Err(e) = product.validate() {
+            return unprocessable(e)
+        }
+
+        match Product::update(pool.deref(), &product).await {
+            Ok(_) => updated_product(pool.deref(), id.as_str()).await,
+            Err(e) => {
+                error!(target: "EditProduct", "Cannot update the product '{id}': {e:#?}");
+                Json(ApiError::new(4013, "Cannot update the product")).into_response()
+            }
+        }
+    }
+
+    /// Answer with the product as stored after an update, with its images and rating which the form does not carry
+    async fn updated_product(pool: &sqlx::MySqlPool, id: &str) -> Response {
+        match Product::find_product(pool, id).await {
+            Ok(Some(product)) => Json(product).into_response(),
+            Ok(None) => product_not_found(),
+            Err(e) => {
+                error!(target: "UpdateProduct", "Cannot fetch the updated product '{id}': {e:#?}");
+                Json(ApiError::new(4013, "Cannot fetch the updated product")).into_response()
+            }
+        }
+    }
+
+    /// Route: DELETE /product/:id
+    ///
+    /// Delete a product, restricted to the admins
+    pub(crate) async fn delete_product(
+        State(app_state): State<AppState>,
+        Path(id): Path<String>,
+        _: Admin
+    ) -> impl IntoResponse
+    {
+        let pool = app_state.database.get_pool().await;
+
+        match Product::delete(pool.deref(), id.as_str()).await {
+            Ok(true) => Json(json!({"message": "Product deleted", "code": 4101})).into_response(),
+            Ok(false) => product_not_found(),
+            Err(e) => {
+                error!(target: "DeleteProduct", "Cannot delete the product '{id}': {e:#?}");
+                Json(ApiError::new(4014, "Cannot delete the product")).into_response()
+            }
+        }
+    }
+
+    /// Fetch the product of the route, or build the response to return
+    ///
+    /// `error_code` and `message` describe the failure of the route if the product cannot be fetched
+    pub(crate) async fn fetch_product(pool: &sqlx::MySqlPool, id: &str, error_code: u16, message: &str) -> Result<Product, Response> {
+        match Product::find_product(pool, id).await {
+            Ok(Some(product)) => Ok(product),
+            Ok(None) => Err(product_not_found()),
+            Err(e) => {
+                error!(target: "FetchProduct", "Cannot fetch the product '{id}': {e:#?}");
+                Err(Json(ApiError::new(error_code, message)).into_response())
+            }
+        }
+    }
+
+    fn characteristic_not_found() -> Response {
+        (StatusCode::NOT_FOUND, Json(ApiError::new(4017, "This characteristic does not exist"))).into_response()
+    }
+
+    fn characteristic_conflict() -> Response {
+        (StatusCode::CONFLICT, Json(ApiError::new(4018, "This product already has a characteristic with this name"))).into_response()
+    }
+
+    #[derive(Serialize, Deserialize, Clone)]
+    pub(crate) struct CharacteristicForm {
+        name: String,
+        detail: String,
+        position: Option<u32>
+    }
+
+    /// Route: POST /product/:id/characteristics
+    ///
+    /// Add a characteristic to a product, restricted to the admins
+    pub(crate) async fn add_product_characteristic(
+        State(app_state): State<AppState>,
+        Path(product): Path<String>,
+        _: Admin,
+        Form(form): Form<CharacteristicForm>
+    ) -> impl IntoResponse
+    {
+        let name = form.name.trim();
+        if let Err(e) = ProductCharacteristic::validate(name, form.detail.as_str()) {
+            return unprocessable(e)
+        }
+
+        let pool = app_state.database.get_pool().await;
+
+        if let Err(res) = fetch_product(pool.deref(), product.as_str(), 4019, "Cannot add the characteristic").await {
+            return res
+        }
+
+        match ProductCharacteristic::add(pool.deref(), product.as_str(), name, form.detail.as_str(), form.position).await {
+            Ok(characteristic) => (StatusCode::CREATED, Json(characteristic)).into_response(),
+            Err(e) if is_unique_violation(&e) => characteristic_conflict(),
+            Err(e) => {
+                error!(target: "AddProductCharacteristic", "Cannot add the characteristic: {e:#?}");
+                Json(ApiError::new(4019, "Cannot add the characteristic")).into_response()
+            }
+        }
+    }
+
+    /// Every field is optional, a new `name` renames the characteristic
+    #[derive(Serialize, Deserialize, Clone)]
+    pub(crate) struct CharacteristicPatchForm {
+        name: Option<String>,
+        detail: Option<String>,
+        position: Option<u32>
+    }
+
+    /// Route: PATCH /product/:id/characteristics/:name
+    ///
+    /// Rename or edit a characteristic of a product, restricted to the admins
+    pub(crate) async fn edit_product_characteristic(
+        State(app_state): State<AppState>,
+        Path((product, name)): Path<(String, String)>,
+        _: Admin,
+        Form(form): Form<CharacteristicPatchForm>
+    ) -> impl IntoResponse
+    {
+        let pool = app_state.database.get_pool().await;
+
+        let old = match ProductCharacteristic::find(pool.deref(), product.as_str(), name.as_str()).await {
+            Ok(Some(characteristic)) => characteristic,
+            Ok(None) => return characteristic_not_found(),
+            Err(e) => {
+                error!(target: "EditProductCharacteristic", "Cannot fetch the characteristic: {e:#?}");
+                return Json(ApiError::new(4020, "Cannot edit the characteristic")).into_response()
+            }
+        };
+
+        let characteristic = ProductCharacteristic {
+            name: form.name.map(|n| n.trim().to_string()).unwrap_or_else(|| old.name.clone()),
+            detail: form.detail.unwrap_or_else(|| old.detail.clone()),
+            position: form.position.unwrap_or(old.position),
+            product: old.product.clone()
+        };
+
+        if let Err(e) = ProductCharacteristic::validate(characteristic.name.as_str(), characteristic.detail.as_str()) {
+            return unprocessable(e)
+        }
+
+        // a rename changing only the case keeps the same row, the names are compared without case
+        match ProductCharacteristic::update(pool.deref(), old.name.as_str(), &characteristic).await {
+            Ok(_) => Json(characteristic).into_response(),
+            Err(e) if is_unique_violation(&e) => characteristic_conflict(),
+            Err(e) => {
+                error!(target: "EditProductCharacteristic", "Cannot edit the characteristic: {e:#?}");
+                Json(ApiError::new(4020, "Cannot edit the characteristic")).into_response()
+            }
+        }
+    }
+
+    /// Route: DELETE /product/:id/characteristics/:name
+    ///
+    /// Remove a characteristic of a product, restricted to the admins
+    pub(crate) async fn delete_product_characteristic(
+        State(app_state): State<AppState>,
+        Path((product, name)): Path<(String, String)>,
+        _: Admin
+    ) -> impl IntoResponse
+    {
+        let pool = app_state.database.get_pool().await;
+
+        match ProductCharacteristic::delete(pool.deref(), product.as_str(), name.as_str()).await {
+            Ok(true) => Json(json!({"message": "Characteristic deleted", "code": 4102})).into_response(),
+            Ok(false) => characteristic_not_found(),
+            Err(e) => {
+                error!(target: "DeleteProductCharacteristic", "Cannot delete the characteristic: {e:#?}");
+                Json(ApiError::new(4021, "Cannot delete the characteristic")).into_response()
+            }
+        }
+    }
+
+    /// Route: PUT /product/:id/characteristics/order
+    ///
+    /// Reorder the characteristics of a product, the JSON body is the list of every characteristic name in the new order
+    pub(crate) async fn reorder_product_characteristics(
+        State(app_state): State<AppState>,
+        Path(product): Path<String>,
+        _: Admin,
+        Json(names): Json<Vec<String>>
+    ) -> impl IntoResponse
+    {
+        let pool = app_state.database.get_pool().await;
+
+        if let Err(res) = fetch_product(pool.deref(), product.as_str(), 4022, "Cannot reorder the characteristics").await {
+            return res
+        }
+
+        match ProductCharacteristic::reorder(pool.deref(), product.as_str(), names.as_slice()).await {
+            Ok(true) => {},
+            Ok(false) => {
+                return unprocessable(ApiError::new(4023, "The list must contain every characteristic of the product exactly once"))
+            }
+            Err(e) => {
+                error!(target: "ReorderProductCharacteristics", "Cannot reorder the characteristics: {e:#?}");
+                return Json(ApiError::new(4022, "Cannot reorder the characteristics")).into_response()
+            }
+        }
+
+        match ProductCharacteristic::get_product_characteristics(pool.deref(), product.as_str()).await {
+            Ok(characteristics) => Json(characteristics).into_response(),
+            Err(e) => {
+                error!(target: "ReorderProductCharacteristics", "Cannot fetch the characteristics: {e:#?}");
+                Json(ApiError::new(4004, "Cannot get the product characteristics")).into_response()
+            }
+        }
+    }
+
+    #[derive(Serialize, Deserialize, Clone)]
+    pub(crate) struct CharacteristicInput {
+        name: String,
+        detail: String
+    }
+
+    /// Route: PUT /product/:id/characteristics
+    ///
+    /// Replace every characteristic of a product, restricted to the admins
+    ///
+    /// The JSON body is the full list of characteristics, their order gives their position
+    pub(crate) async fn replace_product_characteristics(
+        State(app_state): State<AppState>,
+        Path(product): Path<String>,
+        _: Admin,
+        Json(inputs): Json<Vec<CharacteristicInput>>
+    ) -> impl IntoResponse
+    {
+        let mut characteristics: Vec<ProductCharacteristic> = Vec::with_capacity(inputs.len());
+
+        for (position, input) in inputs.into_iter().enumerate() {
+            let name = input.name.trim().to_string();
+            if let Err(e) = ProductCharacteristic::validate(name.as_str(), input.detail.as_str()) {
+                return unprocessable(e)
+            }
+            // the names are compared without case, like the primary key of the table
+            if characteristics.iter().any(|c| c.name.to_lowercase() == name.to_lowercase()) {
+                return characteristic_conflict()
+            }
+
+            characteristics.push(ProductCharacteristic {
+                product: product.clone(),
+                name,
+                detail: input.detail,
+                position: position as u32
+            });
+        }
+
+        let pool = app_state.database.get_pool().await;
+
+        if let Err(res) = fetch_product(pool.deref(), product.as_str(), 4024, "Cannot replace the characteristics").await {
+            return res
+        }
+
+        match ProductCharacteristic::replace_all(pool.deref(), product.as_str(), characteristics.as_slice()).await {
+            Ok(_) => Json(characteristics).into_response(),
+            Err(e) if is_unique_violation(&e) => characteristic_conflict(),
+            Err(e) => {
+                error!(target: "ReplaceProductCharacteristics", "Cannot replace the characteristics: {e:#?}");
+                Json(ApiError::new(4024, "Cannot replace the characteristics")).into_response()
+            }
+        }
+    }
 }
\ No newline at end of file
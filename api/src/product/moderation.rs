@@ -0,0 +1,223 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{MySql, MySqlPool, QueryBuilder};
+use crate::constants::REJECTION_REASON_MAX_LENGTH;
+use crate::database::string_enum_type;
+use crate::declaration::ApiError;
+use crate::pagination::{Cursor, Page, Pagination};
+use crate::product::{Review, REVIEWS_QUERY};
+
+/// Moderation state of a review, stored in the `reviews.status` column
+///
+/// Only the approved reviews are shown to the public and counted in the ratings
+#[derive(sqlx::Encode, sqlx::Decode, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[sqlx(rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum ReviewStatus {
+    #[default]
+    Pending,
+    Approved,
+    Rejected
+}
+
+string_enum_type!(ReviewStatus);
+
+/// Moderation of the reviews, the `moderation` entry of the api config
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub(crate) struct ModerationConfig {
+    /// Publish the new reviews without waiting for a moderator
+    pub auto_approve: bool,
+    /// A review containing one of these words always waits for a moderator, the case is ignored
+    pub banned_words: Vec<String>
+}
+
+impl Default for ModerationConfig {
+    fn default() -> Self {
+        Self {
+            auto_approve: true,
+            banned_words: Vec::new()
+        }
+    }
+}
+
+impl ModerationConfig {
+    fn contains_banned_word(&self, text: &str) -> bool {
+        text.split(|c: char| !c.is_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .any(|word| self.banned_words.iter().any(|banned| banned.to_lowercase() == word.to_lowercase()))
+    }
+
+    /// Status of a new review with this text
+    pub(crate) fn initial_status(&self, text: &str) -> ReviewStatus {
+        if self.auto_approve && !self.contains_banned_word(text) {
+            ReviewStatus::Approved
+        } else {
+            ReviewStatus::Pending
+        }
+    }
+}
+
+impl Review {
+    /// Fetch a page of the reviews with this status, the oldest first
+    async fn get_by_status(pool: &MySqlPool, status: ReviewStatus, pagination: Pagination) -> Result<Page<Self>, sqlx::Error> {
+        let total: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM reviews WHERE status = ?")
+            .bind(status)
+            .fetch_one(pool)
+            .await?;
+
+        let mut builder: QueryBuilder<MySql> = QueryBuilder::new(REVIEWS_QUERY);
+        builder.push(" WHERE reviews.status = ").push_bind(status);
+
+        // keyset pagination: only the reviews newer than the last one of the previous page
+        if let Some(Cursor { key: Some(created_at), id }) = &pagination.cursor {
+            builder.push(" AND (reviews.created_at > ").push_bind(created_at.clone());
+            builder.push(" OR (reviews.created_at = ").push_bind(created_at.clone());
+            builder.push(" AND reviews.id > ").push_bind(id.clone()).push("))");
+        }
+
+        builder.push(" ORDER BY reviews.created_at, reviews.id");
+        builder.push(" LIMIT ").push_bind(pagination.page_size + 1);
+        builder.push(" OFFSET ").push_bind(pagination.offset());
+
+        let reviews = builder.build_query_as::<Self>()
+            .fetch_all(pool)
+            .await?;
+
+        Ok(pagination.into_page(reviews, total as u64, |review| Cursor {
+            key: Some(review.created_at.format("%Y-%m-%d %H:%M:%S").to_string()),
+            id: review.id.clone()
+        }))
+    }
+
+    /// Approve or reject the review, return `false` if there is no review with this id
+    async fn moderate(pool: &MySqlPool, id: &str, status: ReviewStatus, reason: Option<&str>) -> Result<bool, sqlx::Error> {
+        sqlx::query("UPDATE reviews SET status = ?, rejection_reason = ? WHERE id = ?")
+            .bind(status)
+            .bind(reason)
+            .bind(id)
+            .execute(pool)
+            .await
+            .map(|res| res.rows_affected() > 0)
+    }
+
+    fn validate_reason(reason: &str) -> Result<(), ApiError> {
+        if reason.is_empty() || reason.chars().count() > REJECTION_REASON_MAX_LENGTH {
+            return Err(ApiError::new(4061, format!("The reason must contain between 1 and {REJECTION_REASON_MAX_LENGTH} characters")))
+        }
+        Ok(())
+    }
+}
+
+/// Contain every routes for axum
+pub(crate) mod routes {
+    use std::ops::Deref;
+    use axum::extract::{Path, Query, State};
+    use axum::{Form, Json};
+    use axum::http::StatusCode;
+    use axum::response::{IntoResponse, Response};
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+    use tracing::error;
+    use crate::{AppState, constants};
+    use crate::clients::role::Staff;
+    use crate::declaration::{ApiError, unprocessable};
+    use crate::pagination::Pagination;
+    use crate::product::moderation::ReviewStatus;
+    use crate::product::Review;
+
+    fn review_not_found() -> Response {
+        (StatusCode::NOT_FOUND, Json(ApiError::new(4059, "This review does not exist"))).into_response()
+    }
+
+    #[derive(Serialize, Deserialize, Clone)]
+    pub(crate) struct ModerationQueueForm {
+        /// The pending reviews by default
+        #[serde(default)]
+        status: ReviewStatus,
+        #[serde(default)]
+        page: u64,
+        page_size: Option<u64>,
+        cursor: Option<String>
+    }
+
+    /// Route: GET /admin/reviews
+    ///
+    /// Get a page of the reviews waiting for a moderator, or with the requested status, the oldest first
+    pub(crate) async fn get_moderation_queue(
+        State(app_state): State<AppState>,
+        _: Staff,
+        Query(params): Query<ModerationQueueForm>
+    ) -> impl IntoResponse
+    {
+        let pagination = match Pagination::new(params.page, params.page_size, constants::REVIEWS_PER_PAGE, params.cursor.as_deref())
+            .and_then(Pagination::require_cursor_key)
+        {
+            Ok(pagination) => pagination,
+            Err(e) => return unprocessable(e)
+        };
+
+        let pool = app_state.database.get_pool().await;
+
+        match Review::get_by_status(pool.deref(), params.status, pagination).await {
+            Ok(reviews) => Json(reviews).into_response(),
+            Err(e) => {
+                error!(target: "GetModerationQueue", "Cannot fetch the reviews: {e:#?}");
+                Json(ApiError::new(4058, "Cannot get the moderation queue")).into_response()
+            }
+        }
+    }
+
+    /// Route: POST /admin/reviews/:id/approve
+    ///
+    /// Publish a review, restricted to the staff
+    pub(crate) async fn approve_review(
+        State(app_state): State<AppState>,
+        Path(id): Path<String>,
+        _: Staff
+    ) -> impl IntoResponse
+    {
+        let pool = app_state.database.get_pool().await;
+
+        match Review::moderate(pool.deref(), id.as_str(), ReviewStatus::Approved, None).await {
+            Ok(true) => Json(json!({"message": "Review approved", "code": 4106})).into_response(),
+            Ok(false) => review_not_found(),
+            Err(e) => {
+                error!(target: "ApproveReview", "Cannot approve the review '{id}': {e:#?}");
+                Json(ApiError::new(4060, "Cannot moderate the review")).into_response()
+            }
+        }
+    }
+
+    #[derive(Serialize, Deserialize, Clone)]
+    pub(crate) struct RejectionForm {
+        /// Kept with the review, the author can read it in its data export
+        reason: String
+    }
+
+    /// Route: POST /admin/reviews/:id/reject
+    ///
+    /// Hide a review from the public with a reason, restricted to the staff
+    pub(crate) async fn reject_review(
+        State(app_state): State<AppState>,
+        Path(id): Path<String>,
+        _: Staff,
+        Form(form): Form<RejectionForm>
+    ) -> impl IntoResponse
+    {
+        let reason = form.reason.trim();
+        if let Err(e) = Review::validate_reason(reason) {
+            return unprocessable(e)
+        }
+
+        let pool = app_state.database.get_pool().await;
+
+        match Review::moderate(pool.deref(), id.as_str(), ReviewStatus::Rejected, Some(reason)).await {
+            Ok(true) => Json(json!({"message": "Review rejected", "code": 4107})).into_response(),
+            Ok(false) => review_not_found(),
+            Err(e) => {
+                error!(target: "RejectReview", "Cannot reject the review '{id}': {e:#?}");
+                Json(ApiError::new(4060, "Cannot moderate the review")).into_response()
+            }
+        }
+    }
+}
@@ -0,0 +1,650 @@
+use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+use sqlx::MySqlPool;
+use uuid::Uuid;
+use crate::constants::{SKU_MAX_LENGTH, VARIANT_OPTION_MAX_LENGTH, VARIANT_VALUE_MAX_LENGTH};
+use crate::declaration::ApiError;
+use crate::money::Money;
+use crate::product::ProductId;
+
+pub(crate) type VariantId = String;
+
+/// An option a product is sold in, e.g. the frame size, with its possible values in display order
+#[derive(Serialize, Deserialize, Clone)]
+pub(crate) struct VariantOption {
+    pub name: String,
+    pub values: Vec<String>
+}
+
+/// A purchasable version of a product, the cart articles reference a variant
+///
+/// Every product has at least one variant, the one of a product without options has no value
+#[derive(sqlx::FromRow, Serialize, Deserialize, Clone)]
+pub(crate) struct ProductVariant {
+    pub id: VariantId,
+    pub product: ProductId,
+    pub sku: Option<String>,
+    /// Price of the variant in cents when it differs from the one of the product
+    pub price_override: Option<u64>,
+    /// Price of the variant, read from the `price` and `currency` columns
+    #[sqlx(flatten)]
+    pub price: Money,
+    pub stock: u32,
+    /// Value of each option of the product, e.g. `{"Roues": "29", "Taille": "M"}`
+    #[sqlx(skip)]
+    pub values: BTreeMap<String, String>
+}
+
+/// Variant chosen for a cart article
+pub(crate) enum ResolvedVariant {
+    Found(VariantId),
+    NotFound,
+    /// No variant was given and the product has several of them
+    Ambiguous
+}
+
+const GET_VARIANTS_QUERY: &str = r#"SELECT
+    product_variant.id AS id,
+    product_variant.product AS product,
+    product_variant.sku AS sku,
+    product_variant.price AS price_override,
+    COALESCE(product_variant.price, product.price) AS price,
+    product.currency AS currency,
+    product_variant.stock AS stock
+FROM
+    product_variant
+JOIN product ON product.id = product_variant.product"#;
+
+impl VariantOption {
+    pub(crate) async fn get_of_product(pool: &MySqlPool, product: &str) -> Result<Vec<Self>, sqlx::Error> {
+        let rows: Vec<(String, Option<String>)> = sqlx::query_as(
+            r#"SELECT variant_option.name, variant_option_value.value
+            FROM variant_option
+            LEFT JOIN variant_option_value ON variant_option_value.option_id = variant_option.id
+            WHERE variant_option.product = ?
+            ORDER BY variant_option.position, variant_option.id, variant_option_value.position"#
+        )
+            .bind(product)
+            .fetch_all(pool)
+            .await?;
+
+        let mut options: Vec<Self> = Vec::new();
+        for (name, value) in rows {
+            if options.last().is_none_or(|o| o.name != name) {
+                options.push(Self { name, values: Vec::new() });
+            }
+            if let (Some(option), Some(value)) = (options.last_mut(), value) {
+                option.values.push(value);
+            }
+        }
+
+        Ok(options)
+    }
+
+    /// Check the options against the constraints of the `variant_option` and `variant_option_value` tables
+    fn validate(options: &[Self]) -> Result<(), ApiError> {
+        for (i, option) in options.iter().enumerate() {
+            if option.name.is_empty() || option.name.chars().count() > VARIANT_OPTION_MAX_LENGTH {
+                return Err(ApiError::new(4029, format!("The option names must contain between 1 and {VARIANT_OPTION_MAX_LENGTH} characters")))
+            }
+            if options[..i].iter().any(|o| o.name == option.name) {
+                return Err(ApiError::new(4030, format!("The option '{}' is given twice", option.name)))
+            }
+            if option.values.is_empty() {
+                return Err(ApiError::new(4031, format!("The option '{}' has no value", option.name)))
+            }
+
+            for (j, value) in option.values.iter().enumerate() {
+                if value.is_empty() || value.chars().count() > VARIANT_VALUE_MAX_LENGTH {
+                    return Err(ApiError::new(4032, format!("The option values must contain between 1 and {VARIANT_VALUE_MAX_LENGTH} characters")))
+                }
+                if option.values[..j].contains(value) {
+                    return Err(ApiError::new(4030, format!("The value '{value}' of the option '{}' is given twice", option.name)))
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Replace the options of the product, in one transaction
+    ///
+    /// The options and values kept are only reordered, so the variants using them are not changed.
+    /// Return `false`, without changing anything, if a variant would lose one of its values or miss a value for a new option,
+    /// only the variant without value of a product which had no option is left as it is
+    async fn replace_all(pool: &MySqlPool, product: &str, options: &[Self]) -> Result<bool, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        let with_values: Vec<String> = sqlx::query_scalar(
+            r#"SELECT DISTINCT product_variant_value.variant
+            FROM product_variant_value
+            JOIN product_variant ON product_variant.id = product_variant_value.variant
+            WHERE product_variant.product = ?"#
+        )
+            .bind(product)
+            .fetch_all(&mut *tx)
+            .await?;
+
+        let existing: Vec<(String, String)> = sqlx::query_as("SELECT id, name FROM variant_option WHERE product = ? FOR UPDATE")
+            .bind(product)
+            .fetch_all(&mut *tx)
+            .await?;
+
+        // the values and their use by the variants are deleted by the cascade
+        for (id, name) in &existing {
+            if !options.iter().any(|o| &o.name == name) {
+                sqlx::query("DELETE FROM variant_option WHERE id = ?")
+                    .bind(id)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+        }
+
+        for (position, option) in options.iter().enumerate() {
+            let option_id = match existing.iter().find(|(_, name)| name == &option.name) {
+                Some((id, _)) => {
+                    sqlx::query("UPDATE variant_option SET position = ? WHERE id = ?")
+                        .bind(position as u32)
+                        .bind(id)
+                        .execute(&mut *tx)
+                        .await?;
+                    id.clone()
+                }
+                None => {
+                    let id = Uuid::new_v4().to_string();
+                    sqlx::query("INSERT INTO variant_option (id, product, name, position) VALUE (?, ?, ?, ?)")
+                        .bind(&id)
+                        .bind(product)
+                        .bind(&option.name)
+                        .bind(position as u32)
+                        .execute(&mut *tx)
+                        .await?;
+                    id
+                }
+            };
+
+            let existing_values: Vec<(String, String)> = sqlx::query_as("SELECT id, value FROM variant_option_value WHERE option_id = ?")
+                .bind(&option_id)
+                .fetch_all(&mut *tx)
+                .await?;
+
+            for (id, value) in &existing_values {
+                if !option.values.contains(value) {
+                    sqlx::query("DELETE FROM variant_option_value WHERE id = ?")
+                        .bind(id)
+                        .execute(&mut *tx)
+                        .await?;
+                }
+            }
+
+            for (position, value) in option.values.iter().enumerate() {
+                match existing_values.iter().find(|(_, v)| v == value) {
+                    Some((id, _)) => {
+                        sqlx::query("UPDATE variant_option_value SET position = ? WHERE id = ?")
+                            .bind(position as u32)
+                            .bind(id)
+                            .execute(&mut *tx)
+                            .await?;
+                    }
+                    None => {
+                        sqlx::query("INSERT INTO variant_option_value (id, option_id, value, position) VALUE (?, ?, ?, ?)")
+                            .bind(Uuid::new_v4().to_string())
+                            .bind(&option_id)
+                            .bind(value)
+                            .bind(position as u32)
+                            .execute(&mut *tx)
+                            .await?;
+                    }
+                }
+            }
+        }
+
+        // the values of the variants are still the ones they had if each of them has one value per option
+        let counts: Vec<(String, i64)> = sqlx::query_as(
+            r#"SELECT product_variant_value.variant, COUNT(*)
+            FROM product_variant_value
+            JOIN product_variant ON product_variant.id = product_variant_value.variant
+            WHERE product_variant.product = ?
+            GROUP BY product_variant_value.variant"#
+        )
+            .bind(product)
+            .fetch_all(&mut *tx)
+            .await?;
+
+        let complete = with_values.iter().all(|variant| {
+            counts.iter().find(|(v, _)| v == variant).map_or(0, |(_, count)| *count) == options.len() as i64
+        });
+        if !complete {
+            return Ok(false)
+        }
+
+        tx.commit().await.map(|_| true)
+    }
+}
+
+impl ProductVariant {
+    pub(crate) async fn get_of_product(pool: &MySqlPool, product: &str) -> Result<Vec<Self>, sqlx::Error> {
+        let mut variants = sqlx::query_as::<_, Self>(
+            format!("{GET_VARIANTS_QUERY} WHERE product_variant.product = ? ORDER BY product_variant.sku, product_variant.id").as_str()
+        )
+            .bind(product)
+            .fetch_all(pool)
+            .await?;
+
+        let values: Vec<(String, String, String)> = sqlx::query_as(
+            r#"SELECT product_variant_value.variant, variant_option.name, variant_option_value.value
+            FROM product_variant_value
+            JOIN variant_option_value ON variant_option_value.id = product_variant_value.value
+            JOIN variant_option ON variant_option.id = variant_option_value.option_id
+            WHERE variant_option.product = ?"#
+        )
+            .bind(product)
+            .fetch_all(pool)
+            .await?;
+
+        for (variant, option, value) in values {
+            if let Some(variant) = variants.iter_mut().find(|v| v.id == variant) {
+                variant.values.insert(option, value);
+            }
+        }
+
+        Ok(variants)
+    }
+
+    /// Fetch the variant if it belongs to the product, without its values
+    pub(crate) async fn find(pool: &MySqlPool, product: &str, id: &str) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>(
+            format!("{GET_VARIANTS_QUERY} WHERE product_variant.id = ? AND product_variant.product = ?").as_str()
+        )
+            .bind(id)
+            .bind(product)
+            .fetch_optional(pool)
+            .await
+    }
+
+    /// Find the variant of a cart article, the variant can be omitted for a product with a single variant
+    pub(crate) async fn resolve(pool: &MySqlPool, product: &str, variant: Option<&str>) -> Result<ResolvedVariant, sqlx::Error> {
+        if let Some(variant) = variant {
+            return Ok(match Self::find(pool, product, variant).await? {
+                Some(variant) => ResolvedVariant::Found(variant.id),
+                None => ResolvedVariant::NotFound
+            })
+        }
+
+        let mut ids: Vec<String> = sqlx::query_scalar("SELECT id FROM product_variant WHERE product = ? LIMIT 2")
+            .bind(product)
+            .fetch_all(pool)
+            .await?;
+
+        Ok(match ids.len() {
+            0 => ResolvedVariant::NotFound,
+            1 => ResolvedVariant::Found(ids.remove(0)),
+            _ => ResolvedVariant::Ambiguous
+        })
+    }
+
+    fn validate_fields(sku: Option<&str>, price_override: Option<u64>) -> Result<(), ApiError> {
+        if sku.is_some_and(|s| s.is_empty() || s.chars().count() > SKU_MAX_LENGTH) {
+            return Err(ApiError::new(4033, format!("The SKU must contain between 1 and {SKU_MAX_LENGTH} characters")))
+        }
+        if price_override == Some(0) {
+            return Err(ApiError::new(4009, "The price must be strictly positive"))
+        }
+        Ok(())
+    }
+
+    async fn sku_exists(pool: &MySqlPool, sku: &str, except: Option<&str>) -> Result<bool, sqlx::Error> {
+        sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM product_variant WHERE sku = ? AND id != ?")
+            .bind(sku)
+            .bind(except.unwrap_or_default())
+            .fetch_one(pool)
+            .await
+            .map(|count| count > 0)
+    }
+
+    /// Create the variant with the given option values, in one transaction
+    async fn create(pool: &MySqlPool, variant: &Self) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        sqlx::query("INSERT INTO product_variant (id, product, sku, price, stock) VALUE (?, ?, ?, ?, ?)")
+            .bind(&variant.id)
+            .bind(&variant.product)
+            .bind(&variant.sku)
+            .bind(variant.price_override)
+            .bind(variant.stock)
+            .execute(&mut *tx)
+            .await?;
+
+        for (option, value) in &variant.values {
+            sqlx::query(
+                r#"INSERT INTO product_variant_value (variant, value)
+                SELECT ?, variant_option_value.id
+                FROM variant_option_value
+                JOIN variant_option ON variant_option.id = variant_option_value.option_id
+                WHERE variant_option.product = ? AND variant_option.name = ? AND variant_option_value.value = ?"#
+            )
+                .bind(&variant.id)
+                .bind(&variant.product)
+                .bind(option)
+                .bind(value)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await
+    }
+
+    async fn update(pool: &MySqlPool, variant: &Self) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE product_variant SET sku = ?, price = ?, stock = ? WHERE id = ?")
+            .bind(&variant.sku)
+            .bind(variant.price_override)
+            .bind(variant.stock)
+            .bind(&variant.id)
+            .execute(pool)
+            .await
+            .map(|_| ())
+    }
+
+    /// Delete the variant, its cart articles are deleted by the cascade
+    async fn delete(pool: &MySqlPool, id: &str) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM product_variant WHERE id = ?")
+            .bind(id)
+            .execute(pool)
+            .await
+            .map(|_| ())
+    }
+}
+
+/// Contain every routes for axum
+pub(crate) mod routes {
+    use std::collections::BTreeMap;
+    use std::ops::Deref;
+    use axum::extract::{Path, State};
+    use axum::Json;
+    use axum::http::StatusCode;
+    use axum::response::{IntoResponse, Response};
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+    use tracing::error;
+    use uuid::Uuid;
+    use crate::AppState;
+    use crate::clients::role::Admin;
+    use crate::declaration::{ApiError, double_option, unprocessable};
+    use crate::money::Money;
+    use crate::product::routes::fetch_product;
+    use crate::product::variant::{ProductVariant, VariantOption};
+
+    fn variant_not_found() -> Response {
+        (StatusCode::NOT_FOUND, Json(ApiError::new(4034, "This variant does not exist"))).into_response()
+    }
+
+    fn sku_conflict() -> Response {
+        (StatusCode::CONFLICT, Json(ApiError::new(4035, "Another variant already uses this SKU"))).into_response()
+    }
+
+    #[derive(Serialize, Deserialize, Clone)]
+    pub(crate) struct ProductVariants {
+        options: Vec<VariantOption>,
+        variants: Vec<ProductVariant>
+    }
+
+    /// Route: GET /product/:id/variants
+    ///
+    /// Get the options of a product and all its variants
+    pub(crate) async fn get_product_variants(
+        State(app_state): State<AppState>,
+        Path(product): Path<String>
+    ) -> impl IntoResponse
+    {
+        let pool = app_state.database.get_pool().await;
+
+        let options = VariantOption::get_of_product(pool.deref(), product.as_str()).await;
+        let variants = ProductVariant::get_of_product(pool.deref(), product.as_str()).await;
+
+        match (options, variants) {
+            (Ok(options), Ok(variants)) => Json(ProductVariants { options, variants }).into_response(),
+            (Err(e), _) | (_, Err(e)) => {
+                error!(target: "GetProductVariants", "Cannot fetch the variants of the product '{product}': {e:#?}");
+                Json(ApiError::new(4036, "Cannot get the product variants")).into_response()
+            }
+        }
+    }
+
+    /// Route: PUT /product/:id/options
+    ///
+    /// Replace the options of a product, restricted to the admins
+    ///
+    /// The JSON body is the full list of options with their values, in display order
+    pub(crate) async fn replace_product_options(
+        State(app_state): State<AppState>,
+        Path(product): Path<String>,
+        _: Admin,
+        Json(options): Json<Vec<VariantOption>>
+    ) -> impl IntoResponse
+    {
+        let options: Vec<VariantOption> = options.into_iter()
+            .map(|o| VariantOption {
+                name: o.name.trim().to_string(),
+                values: o.values.iter().map(|v| v.trim().to_string()).collect()
+            })
+            .collect();
+
+        if let Err(e) = VariantOption::validate(options.as_slice()) {
+            return unprocessable(e)
+        }
+
+        let pool = app_state.database.get_pool().await;
+
+        if let Err(res) = fetch_product(pool.deref(), product.as_str(), 4037, "Cannot replace the product options").await {
+            return res
+        }
+
+        match VariantOption::replace_all(pool.deref(), product.as_str(), options.as_slice()).await {
+            Ok(true) => Json(options).into_response(),
+            Ok(false) => {
+                unprocessable(ApiError::new(4068, "The options cannot remove a value used by a variant, nor add an option while variants have values"))
+            }
+            Err(e) => {
+                error!(target: "ReplaceProductOptions", "Cannot replace the options of the product '{product}': {e:#?}");
+                Json(ApiError::new(4037, "Cannot replace the product options")).into_response()
+            }
+        }
+    }
+
+    #[derive(Serialize, Deserialize, Clone)]
+    pub(crate) struct VariantForm {
+        sku: Option<String>,
+        /// In cents, the price of the product is used when absent
+        price: Option<u64>,
+        #[serde(default)]
+        stock: u32,
+        /// A value for each option of the product
+        #[serde(default)]
+        values: BTreeMap<String, String>
+    }
+
+    /// Route: POST /product/:id/variants
+    ///
+    /// Add a variant to a product, restricted to the admins
+    pub(crate) async fn create_product_variant(
+        State(app_state): State<AppState>,
+        Path(product): Path<String>,
+        _: Admin,
+        Json(form): Json<VariantForm>
+    ) -> impl IntoResponse
+    {
+        let sku = form.sku.map(|s| s.trim().to_string());
+        if let Err(e) = ProductVariant::validate_fields(sku.as_deref(), form.price) {
+            return unprocessable(e)
+        }
+
+        let pool = app_state.database.get_pool().await;
+
+        let product = match fetch_product(pool.deref(), product.as_str(), 4038, "Cannot create the variant").await {
+            Ok(product) => product,
+            Err(res) => return res
+        };
+
+        let (options, variants) = match (
+            VariantOption::get_of_product(pool.deref(), product.id.as_str()).await,
+            ProductVariant::get_of_product(pool.deref(), product.id.as_str()).await
+        ) {
+            (Ok(options), Ok(variants)) => (options, variants),
+            (Err(e), _) | (_, Err(e)) => {
+                error!(target: "CreateProductVariant", "Cannot fetch the variants of the product '{}': {e:#?}", product.id);
+                return Json(ApiError::new(4038, "Cannot create the variant")).into_response()
+            }
+        };
+
+        // exactly one existing value for each option of the product
+        let complete = form.values.len() == options.len() && options.iter().all(|o| {
+            form.values.get(&o.name).is_some_and(|v| o.values.contains(v))
+        });
+        if !complete {
+            return unprocessable(ApiError::new(4039, "The variant must have one of the possible values for each option of the product"))
+        }
+
+        if variants.iter().any(|v| v.values == form.values) {
+            return (StatusCode::CONFLICT, Json(ApiError::new(4040, "A variant already has these option values"))).into_response()
+        }
+
+        if let Some(sku) = &sku {
+            match ProductVariant::sku_exists(pool.deref(), sku.as_str(), None).await {
+                Ok(false) => {},
+                Ok(true) => return sku_conflict(),
+                Err(e) => {
+                    error!(target: "CreateProductVariant", "Cannot check the SKU '{sku}': {e:#?}");
+                    return Json(ApiError::new(4038, "Cannot create the variant")).into_response()
+                }
+            }
+        }
+
+        let variant = ProductVariant {
+            id: Uuid::new_v4().to_string(),
+            product: product.id.clone(),
+            sku,
+            price_override: form.price,
+            price: Money::new(form.price.unwrap_or(product.price.cents), product.price.currency),
+            stock: form.stock,
+            values: form.values
+        };
+
+        match ProductVariant::create(pool.deref(), &variant).await {
+            Ok(_) => (StatusCode::CREATED, Json(variant)).into_response(),
+            Err(e) => {
+                error!(target: "CreateProductVariant", "Cannot create the variant: {e:#?}");
+                Json(ApiError::new(4038, "Cannot create the variant")).into_response()
+            }
+        }
+    }
+
+    /// Every field is optional, a `null` SKU or price removes it
+    ///
+    /// The values of a variant cannot change, another variant must be created instead
+    #[derive(Serialize, Deserialize, Clone)]
+    pub(crate) struct VariantPatchForm {
+        #[serde(default, deserialize_with = "double_option")]
+        sku: Option<Option<String>>,
+        #[serde(default, deserialize_with = "double_option")]
+        price: Option<Option<u64>>,
+        stock: Option<u32>
+    }
+
+    /// Route: PATCH /product/:id/variants/:variant
+    ///
+    /// Change the SKU, the price or the stock of a variant, restricted to the admins
+    pub(crate) async fn edit_product_variant(
+        State(app_state): State<AppState>,
+        Path((product, variant)): Path<(String, String)>,
+        _: Admin,
+        Json(form): Json<VariantPatchForm>
+    ) -> impl IntoResponse
+    {
+        let pool = app_state.database.get_pool().await;
+
+        let product = match fetch_product(pool.deref(), product.as_str(), 4041, "Cannot edit the variant").await {
+            Ok(product) => product,
+            Err(res) => return res
+        };
+
+        let mut variant = match ProductVariant::find(pool.deref(), product.id.as_str(), variant.as_str()).await {
+            Ok(Some(variant)) => variant,
+            Ok(None) => return variant_not_found(),
+            Err(e) => {
+                error!(target: "EditProductVariant", "Cannot fetch the variant '{variant}': {e:#?}");
+                return Json(ApiError::new(4041, "Cannot edit the variant")).into_response()
+            }
+        };
+
+        if let Some(sku) = form.sku {
+            variant.sku = sku.map(|s| s.trim().to_string());
+        }
+        if let Some(price) = form.price {
+            variant.price_override = price;
+            variant.price = Money::new(price.unwrap_or(product.price.cents), product.price.currency);
+        }
+        if let Some(stock) = form.stock {
+            variant.stock = stock;
+        }
+
+        if let Err(e) = ProductVariant::validate_fields(variant.sku.as_deref(), variant.price_override) {
+            return unprocessable(e)
+        }
+
+        if let Some(sku) = &variant.sku {
+            match ProductVariant::sku_exists(pool.deref(), sku.as_str(), Some(variant.id.as_str())).await {
+                Ok(false) => {},
+                Ok(true) => return sku_conflict(),
+                Err(e) => {
+                    error!(target: "EditProductVariant", "Cannot check the SKU '{sku}': {e:#?}");
+                    return Json(ApiError::new(4041, "Cannot edit the variant")).into_response()
+                }
+            }
+        }
+
+        match ProductVariant::update(pool.deref(), &variant).await {
+            Ok(_) => Json(variant).into_response(),
+            Err(e) => {
+                error!(target: "EditProductVariant", "Cannot edit the variant '{}': {e:#?}", variant.id);
+                Json(ApiError::new(4041, "Cannot edit the variant")).into_response()
+            }
+        }
+    }
+
+    /// Route: DELETE /product/:id/variants/:variant
+    ///
+    /// Delete a variant, restricted to the admins
+    ///
+    /// The last variant of a product cannot be deleted, the product could not be bought anymore
+    pub(crate) async fn delete_product_variant(
+        State(app_state): State<AppState>,
+        Path((product, variant)): Path<(String, String)>,
+        _: Admin
+    ) -> impl IntoResponse
+    {
+        let pool = app_state.database.get_pool().await;
+
+        let variants = match ProductVariant::get_of_product(pool.deref(), product.as_str()).await {
+            Ok(variants) => variants,
+            Err(e) => {
+                error!(target: "DeleteProductVariant", "Cannot fetch the variants of the product '{product}': {e:#?}");
+                return Json(ApiError::new(4042, "Cannot delete the variant")).into_response()
+            }
+        };
+
+        if !variants.iter().any(|v| v.id == variant) {
+            return variant_not_found()
+        }
+        if variants.len() == 1 {
+            return (StatusCode::CONFLICT, Json(ApiError::new(4043, "The last variant of a product cannot be deleted"))).into_response()
+        }
+
+        match ProductVariant::delete(pool.deref(), variant.as_str()).await {
+            Ok(_) => Json(json!({"message": "Variant deleted", "code": 4103})).into_response(),
+            Err(e) => {
+                error!(target: "DeleteProductVariant", "Cannot delete the variant '{variant}': {e:#?}");
+                Json(ApiError::new(4042, "Cannot delete the variant")).into_response()
+            }
+        }
+    }
+}
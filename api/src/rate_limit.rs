@@ -0,0 +1,184 @@
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use axum::extract::{ConnectInfo, FromRequestParts, State};
+use axum::http::{header, Method, Request, StatusCode};
+use axum::Json;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use serde::{Deserialize, Serialize};
+use crate::AppState;
+use crate::clients::Client;
+use crate::declaration::ApiError;
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub(crate) struct RateLimitConfig {
+    /// Number of write requests that can be made at once
+    pub burst: u32,
+    /// Number of write requests regained per minute
+    pub per_minute: u32,
+    /// Number of failed logins on a username before it is locked
+    pub login_max_failures: u32,
+    /// Duration of the first lockout, doubled on each new failure
+    pub login_lockout_seconds: u64,
+    pub login_lockout_max_seconds: u64
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            burst: 20,
+            per_minute: 60,
+            login_max_failures: 5,
+            login_lockout_seconds: 30,
+            login_lockout_max_seconds: 60 * 60
+        }
+    }
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant
+}
+
+struct LoginFailures {
+    count: u32,
+    last_failure: Instant,
+    locked_until: Option<Instant>
+}
+
+/// In-process token buckets limiting the write requests, and lockout of the usernames after failed logins
+pub(crate) struct RateLimiter {
+    config: RateLimitConfig,
+    buckets: Mutex<HashMap<String, Bucket>>,
+    login_failures: Mutex<HashMap<String, LoginFailures>>
+}
+
+impl RateLimiter {
+    pub(crate) fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config,
+            buckets: Mutex::new(HashMap::new()),
+            login_failures: Mutex::new(HashMap::new())
+        }
+    }
+
+    fn refill_rate(&self) -> f64 {
+        self.config.per_minute as f64 / 60.0
+    }
+
+    /// Take a token from the bucket of the key, or return how long to wait before the next one
+    pub(crate) fn check(&self, key: &str) -> Result<(), Duration> {
+        let capacity = self.config.burst as f64;
+        let rate = self.refill_rate();
+        let now = Instant::now();
+
+        let mut buckets = self.buckets.lock().unwrap();
+        let bucket = buckets.entry(key.to_string())
+            .or_insert(Bucket { tokens: capacity, updated: now });
+
+        let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * rate).min(capacity);
+        bucket.updated = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else if rate > 0.0 {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / rate))
+        } else {
+            Err(Duration::from_secs(60))
+        }
+    }
+
+    /// Return how long the username is still locked, if it is
+    pub(crate) fn login_lockout(&self, username: &str) -> Option<Duration> {
+        let failures = self.login_failures.lock().unwrap();
+        failures.get(&username.to_lowercase())
+            .and_then(|f| f.locked_until)
+            .and_then(|until| until.checked_duration_since(Instant::now()))
+    }
+
+    /// Count a failed login, the username is locked for a longer time on each failure past the limit
+    pub(crate) fn login_failed(&self, username: &str) {
+        let mut failures = self.login_failures.lock().unwrap();
+        let entry = failures.entry(username.to_lowercase())
+            .or_insert(LoginFailures { count: 0, last_failure: Instant::now(), locked_until: None });
+
+        entry.count += 1;
+        entry.last_failure = Instant::now();
+
+        if entry.count >= self.config.login_max_failures {
+            let exponent = (entry.count - self.config.login_max_failures).min(16);
+            let seconds = self.config.login_lockout_seconds
+                .saturating_mul(1 << exponent)
+                .min(self.config.login_lockout_max_seconds);
+
+            entry.locked_until = Some(Instant::now() + Duration::from_secs(seconds));
+        }
+    }
+
+    pub(crate) fn login_succeeded(&self, username: &str) {
+        self.login_failures.lock().unwrap().remove(&username.to_lowercase());
+    }
+
+    /// Forget the full buckets and the old failed logins, to keep the memory bounded
+    pub(crate) fn prune(&self) {
+        let capacity = self.config.burst as f64;
+        let rate = self.refill_rate();
+        let now = Instant::now();
+        let failures_memory = Duration::from_secs(self.config.login_lockout_max_seconds);
+
+        self.buckets.lock().unwrap()
+            .retain(|_, b| b.tokens + now.duration_since(b.updated).as_secs_f64() * rate < capacity);
+
+        self.login_failures.lock().unwrap()
+            .retain(|_, f| {
+                f.locked_until.is_some_and(|until| until > now) || now.duration_since(f.last_failure) < failures_memory
+            });
+    }
+}
+
+/// Build the `429 Too Many Requests` response, with the `Retry-After` header
+pub(crate) fn too_many_requests(retry_after: Duration, code: u16, message: &str) -> Response {
+    (
+        StatusCode::TOO_MANY_REQUESTS,
+        [(header::RETRY_AFTER, retry_after.as_secs().max(1).to_string())],
+        Json(ApiError::new(code, message))
+    ).into_response()
+}
+
+/// Middleware limiting the write requests, per IP address and per authenticated client
+///
+/// The reading requests are not limited
+pub(crate) async fn rate_limit_layer<B: Send>(
+    State(app_state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    req: Request<B>,
+    next: Next<B>
+) -> Response
+{
+    if matches!(*req.method(), Method::GET | Method::HEAD | Method::OPTIONS) {
+        return next.run(req).await
+    }
+
+    let limiter = &app_state.rate_limiter;
+
+    if let Err(retry_after) = limiter.check(format!("ip:{}", addr.ip()).as_str()) {
+        return too_many_requests(retry_after, 1001, "Too many requests, retry later")
+    }
+
+    // the resolved client stays in the request extensions, the handler will not fetch it again
+    let (mut parts, body) = req.into_parts();
+    let client = Option::<Client>::from_request_parts(&mut parts, &app_state).await.unwrap_or(None);
+
+    if let Some(client) = client {
+        if let Err(retry_after) = limiter.check(format!("client:{}", client.id).as_str()) {
+            return too_many_requests(retry_after, 1001, "Too many requests, retry later")
+        }
+    }
+
+    next.run(Request::from_parts(parts, body)).await
+}
@@ -0,0 +1,138 @@
+use axum::async_trait;
+use axum::extract::{FromRequestParts, State};
+use axum::http::{header, HeaderMap, HeaderValue, Request, StatusCode};
+use axum::http::request::Parts;
+use axum::Json;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use sqlx::MySqlPool;
+use tracing::error;
+use uuid::Uuid;
+use crate::AppState;
+use crate::clients::Client;
+use crate::clients::auth::read_token;
+use crate::constants::{GUEST_COOKIE, GUEST_COOKIE_DURATION_SECONDS};
+use crate::declaration::ApiError;
+use crate::shopping_cart::ShoppingCart;
+
+/// Identifier of an anonymous visitor, used in place of a client id for its shopping cart
+#[derive(Clone)]
+pub(crate) struct GuestId(pub String);
+
+fn signature(key: &[u8], guest: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any size");
+    mac.update(guest.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// Build the `Set-Cookie` value holding the signed guest id
+pub(crate) fn guest_cookie(key: &[u8], guest: &str) -> String {
+    format!(
+        "{GUEST_COOKIE}={guest}.{signature}; Path=/; Max-Age={GUEST_COOKIE_DURATION_SECONDS}; HttpOnly; SameSite=Lax",
+        signature = signature(key, guest)
+    )
+}
+
+/// Build the `Set-Cookie` value removing the guest id from the browser
+pub(crate) fn expired_guest_cookie() -> String {
+    format!("{GUEST_COOKIE}=; Path=/; Max-Age=0; HttpOnly; SameSite=Lax")
+}
+
+/// Read the guest id from the cookies of the request, `None` if there is none or if its signature is wrong
+pub(crate) fn read_guest(key: &[u8], headers: &HeaderMap) -> Option<GuestId> {
+    let value = headers.get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .filter_map(|cookie| cookie.trim().split_once('='))
+        .find(|(name, _)| *name == GUEST_COOKIE)
+        .map(|(_, value)| value)?;
+
+    let (guest, signed) = value.split_once('.')?;
+    let signed = hex::decode(signed).ok()?;
+
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).ok()?;
+    mac.update(guest.as_bytes());
+    mac.verify_slice(signed.as_slice()).ok()?;
+
+    Some(GuestId(guest.to_string()))
+}
+
+/// Middleware of the shopping cart routes giving a signed guest id to the anonymous visitors
+///
+/// The guest id is available to the handlers through the request extensions
+pub(crate) async fn guest_layer<B>(
+    State(app_state): State<AppState>,
+    mut req: Request<B>,
+    next: Next<B>
+) -> Response
+{
+    let key = app_state.cookie_key.as_slice();
+
+    if let Some(guest) = read_guest(key, req.headers()) {
+        req.extensions_mut().insert(guest);
+        return next.run(req).await
+    }
+
+    // an authenticated client does not need a guest cart
+    if read_token(req.headers()).is_some() {
+        return next.run(req).await
+    }
+
+    let guest = GuestId(Uuid::new_v4().to_string());
+    req.extensions_mut().insert(guest.clone());
+
+    let mut res = next.run(req).await;
+    if let Ok(cookie) = HeaderValue::from_str(guest_cookie(key, guest.0.as_str()).as_str()) {
+        res.headers_mut().append(header::SET_COOKIE, cookie);
+    }
+    res
+}
+
+/// Owner of the shopping cart used by a request: the authenticated client, or else the guest
+pub(crate) struct CartOwner(pub String);
+
+#[async_trait]
+impl FromRequestParts<AppState> for CartOwner {
+    type Rejection = Response;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        match Client::from_request_parts(parts, state).await {
+            Ok(client) => return Ok(Self(client.id)),
+            // a server error must not silently fall back to the guest cart
+            Err(rejection) if rejection.status() != StatusCode::UNAUTHORIZED => return Err(rejection),
+            Err(_) => {}
+        }
+
+        match parts.extensions.get::<GuestId>() {
+            Some(guest) => Ok(Self(guest.0.clone())),
+            None => Err(
+                (
+                    StatusCode::UNAUTHORIZED,
+                    Json(ApiError::new(6007, "Authentication required"))
+                ).into_response()
+            )
+        }
+    }
+}
+
+/// Move the articles of the guest cart of the request into the cart of the client
+///
+/// Return whether a guest cart was merged, in which case its cookie should be removed from the browser
+///
+/// On failure the cookie is kept, so that the guest cart is still reachable and merged on the next login
+pub(crate) async fn merge_guest_cart(pool: &MySqlPool, key: &[u8], headers: &HeaderMap, client: &str) -> bool {
+    let Some(guest) = read_guest(key, headers) else {
+        return false
+    };
+
+    match ShoppingCart::merge(pool, guest.0.as_str(), client).await {
+        Ok(_) => true,
+        Err(e) => {
+            error!(target: "MergeGuestCart", "Cannot merge the guest cart '{}' into the cart of '{client}': {e:#?}", guest.0);
+            false
+        }
+    }
+}
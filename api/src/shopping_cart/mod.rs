@@ -2,19 +2,29 @@ use serde::{Deserialize, Serialize};
 use sqlx::{MySqlPool, Row};
 use uuid::Uuid;
 use crate::clients::ClientId;
+use crate::constants::GUEST_COOKIE_DURATION_SECONDS;
+use crate::money::Money;
 use crate::product::ProductId;
+use crate::product::variant::VariantId;
+
+pub(crate) mod guest;
 
 pub(crate) type ShoppingCartId = String;
 
 #[derive(sqlx::FromRow, Serialize, Deserialize, Clone)]
 pub(crate) struct ShoppingCart {
     pub user: ClientId,
+    /// Empty while the cart is not stored, it is only stored once an article is added
     pub id: ShoppingCartId,
-    pub articles: Vec<ShoppingCartArticles>
+    pub articles: Vec<ShoppingCartArticles>,
+    /// Total price of the articles, one amount per currency
+    pub totals: Vec<Money>
 }
 
 impl ShoppingCart {
     async fn create(pool: &MySqlPool, user: &str) -> Result<Uuid, sqlx::Error> {
+        Self::delete_expired_guest_carts(pool).await?;
+
         let id = Uuid::new_v4();
         sqlx::query("INSERT INTO shopping_cart (user, id) VALUE (?, ?)")
             .bind(user)
@@ -24,7 +34,31 @@ impl ShoppingCart {
             .map(|_| id)
     }
 
-    async fn fetch_user_cart(pool: &MySqlPool, user: &str) -> Result<Option<Self>, sqlx::Error> {
+    /// Delete the carts of the guests whose cookie has expired, they cannot be reached anymore
+    ///
+    /// A guest cart belongs to an id which is not the one of a client, the cart of a client is kept
+    async fn delete_expired_guest_carts(pool: &MySqlPool) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"DELETE FROM shopping_cart
+            WHERE created_at <= DATE_SUB(NOW(), INTERVAL ? SECOND) AND user NOT IN (SELECT id FROM clients)"#
+        )
+            .bind(GUEST_COOKIE_DURATION_SECONDS)
+            .execute(pool)
+            .await
+            .map(|_| ())
+    }
+
+    /// The cart of a user who has not added any article yet, it is not stored
+    fn empty(user: &str) -> Self {
+        Self {
+            user: user.to_string(),
+            id: String::new(),
+            articles: Vec::new(),
+            totals: Vec::new()
+        }
+    }
+
+    pub(crate) async fn fetch_user_cart(pool: &MySqlPool, user: &str) -> Result<Option<Self>, sqlx::Error> {
         let row = sqlx::query("SELECT user, id FROM shopping_cart WHERE user = ?")
             .bind(user)
             .fetch_optional(pool)
@@ -37,8 +71,78 @@ impl ShoppingCart {
         let id: String = row.get(1);
 
         let articles = ShoppingCartArticles::fetch_all(pool, id.as_str()).await?;
+        let totals = Self::totals(articles.as_slice());
+
+        Ok(Some(Self { user, id, articles, totals }))
+    }
+
+    fn totals(articles: &[ShoppingCartArticles]) -> Vec<Money> {
+        let mut totals: Vec<Money> = Vec::new();
+
+        for article in articles {
+            let line = article.unit_price.saturating_mul(article.quantity);
+            match totals.iter_mut().find(|total| total.currency == line.currency) {
+                Some(total) => total.cents = total.cents.saturating_add(line.cents),
+                None => totals.push(line)
+            }
+        }
+
+        totals
+    }
+
+    /// Move the cart of the guest to the user, summing the quantities of the variants present in both carts
+    ///
+    /// The summed quantities are capped to the stock of the variants
+    pub(crate) async fn merge(pool: &MySqlPool, guest: &str, user: &str) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        let guest_cart: Option<String> = sqlx::query_scalar("SELECT id FROM shopping_cart WHERE user = ?")
+            .bind(guest)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+        let Some(guest_cart) = guest_cart else { return Ok(()) };
+
+        let user_cart: Option<String> = sqlx::query_scalar("SELECT id FROM shopping_cart WHERE user = ?")
+            .bind(user)
+            .fetch_optional(&mut *tx)
+            .await?;
 
-        Ok(Some(Self { user, id, articles }))
+        match user_cart {
+            // the user has no cart yet, the guest cart simply becomes its cart
+            None => {
+                sqlx::query("UPDATE shopping_cart SET user = ? WHERE id = ?")
+                    .bind(user)
+                    .bind(&guest_cart)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+            Some(user_cart) => {
+                sqlx::query(
+                    r#"INSERT INTO shopping_cart_article (shopping_cart_id, variant, quantity)
+                    SELECT ?, variant, quantity FROM shopping_cart_article WHERE shopping_cart_id = ?
+                    ON DUPLICATE KEY UPDATE quantity = GREATEST(
+                        LEAST(
+                            shopping_cart_article.quantity + VALUES(quantity),
+                            (SELECT stock FROM product_variant WHERE id = shopping_cart_article.variant)
+                        ),
+                        shopping_cart_article.quantity
+                    )"#
+                )
+                    .bind(&user_cart)
+                    .bind(&guest_cart)
+                    .execute(&mut *tx)
+                    .await?;
+
+                // the articles of the guest cart are deleted by the cascade
+                sqlx::query("DELETE FROM shopping_cart WHERE id = ?")
+                    .bind(&guest_cart)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+        }
+
+        tx.commit().await
     }
 
     async fn clear_articles(pool: &MySqlPool, shopping_cart_id: &str) -> Result<(), sqlx::Error> {
@@ -53,64 +157,111 @@ impl ShoppingCart {
 #[derive(sqlx::FromRow, Serialize, Deserialize, Clone)]
 pub(crate) struct ShoppingCartArticles {
     pub product: ProductId,
+    pub variant: VariantId,
+    pub sku: Option<String>,
+    /// Values of the options of the variant, e.g. `Taille: M, Roues: 29`
+    pub variant_label: Option<String>,
     pub quantity: u64,
+    /// Quantity of the variant currently in stock, it can drop below the quantity of the article
+    pub stock: u32,
     pub shopping_cart_id: String,
+    /// Current price of one article, read from the `price` and `currency` columns of the variant and its product
+    #[sqlx(flatten)]
+    pub unit_price: Money
 }
 
 impl ShoppingCartArticles {
     async fn fetch_all(pool: &MySqlPool, shopping_cart_id: &str) -> Result<Vec<Self>, sqlx::Error> {
-        sqlx::query_as::<_, Self>("SELECT * FROM shopping_cart_article WHERE shopping_cart_id = ?;")
+        sqlx::query_as::<_, Self>(r#"SELECT
+                shopping_cart_article.shopping_cart_id,
+                shopping_cart_article.variant,
+                shopping_cart_article.quantity,
+                product_variant.product,
+                product_variant.sku,
+                product_variant.stock,
+                (
+                    SELECT GROUP_CONCAT(CONCAT(variant_option.name, ': ', variant_option_value.value) ORDER BY variant_option.position SEPARATOR ', ')
+                    FROM product_variant_value
+                    JOIN variant_option_value ON variant_option_value.id = product_variant_value.value
+                    JOIN variant_option ON variant_option.id = variant_option_value.option_id
+                    WHERE product_variant_value.variant = shopping_cart_article.variant
+                ) AS variant_label,
+                COALESCE(product_variant.price, product.price) AS price,
+                product.currency
+            FROM shopping_cart_article
+            JOIN product_variant ON product_variant.id = shopping_cart_article.variant
+            JOIN product ON product.id = product_variant.product
+            WHERE shopping_cart_id = ?"#)
             .bind(shopping_cart_id)
             .fetch_all(pool)
             .await
     }
 
-    async fn exists(pool: &MySqlPool, shopping_cart_id: &str, product: &str) -> Result<(), sqlx::Error> {
-        sqlx::query("SELECT * FROM shopping_cart_article WHERE shopping_cart_id = ? AND product = ?")
+    async fn exists(pool: &MySqlPool, shopping_cart_id: &str, variant: &str) -> Result<(), sqlx::Error> {
+        sqlx::query("SELECT * FROM shopping_cart_article WHERE shopping_cart_id = ? AND variant = ?")
             .bind(shopping_cart_id)
-            .bind(product)
+            .bind(variant)
             .fetch_one(pool)
             .await
             .map(|_| ())
     }
 
-    async fn add(pool: &MySqlPool, shopping_cart_id: &str, product: &str, quantity: u64) -> Result<(), sqlx::Error> {
-        sqlx::query("INSERT INTO shopping_cart_article (shopping_cart_id, product, quantity) VALUE (?, ?, ?)")
+    /// Add the article, return `false` without adding it if the quantity exceeds the stock of the variant
+    async fn add(pool: &MySqlPool, shopping_cart_id: &str, variant: &str, quantity: u64) -> Result<bool, sqlx::Error> {
+        sqlx::query(
+            r#"INSERT INTO shopping_cart_article (shopping_cart_id, variant, quantity)
+            SELECT ?, id, ? FROM product_variant WHERE id = ? AND stock >= ?"#
+        )
             .bind(shopping_cart_id)
-            .bind(product)
+            .bind(quantity)
+            .bind(variant)
             .bind(quantity)
             .execute(pool)
             .await
-            .map(|_| ())
+            .map(|res| res.rows_affected() > 0)
     }
 
-    async fn delete(pool: &MySqlPool, shopping_cart_id: &str, product: &str) -> Result<(), sqlx::Error> {
-        sqlx::query("DELETE FROM shopping_cart_article WHERE shopping_cart_id = ? AND product = ?")
+    async fn delete(pool: &MySqlPool, shopping_cart_id: &str, variant: &str) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM shopping_cart_article WHERE shopping_cart_id = ? AND variant = ?")
             .bind(shopping_cart_id)
-            .bind(product)
+            .bind(variant)
             .execute(pool)
             .await
             .map(|_| ())
     }
 
-    async fn add_quantity(pool: &MySqlPool, shopping_cart_id: &str, product: &str, quantity: u64) -> Result<(), sqlx::Error> {
-        sqlx::query("UPDATE shopping_cart_article SET quantity = quantity + ? WHERE shopping_cart_id = ? AND product = ?")
+    /// Increase the quantity, return `false` without changing it if the new quantity exceeds the stock of the variant
+    async fn add_quantity(pool: &MySqlPool, shopping_cart_id: &str, variant: &str, quantity: u64) -> Result<bool, sqlx::Error> {
+        sqlx::query(
+            r#"UPDATE shopping_cart_article
+            JOIN product_variant ON product_variant.id = shopping_cart_article.variant
+            SET quantity = quantity + ?
+            WHERE shopping_cart_id = ? AND variant = ? AND quantity + ? <= product_variant.stock"#
+        )
             .bind(quantity)
             .bind(shopping_cart_id)
-            .bind(product)
+            .bind(variant)
+            .bind(quantity)
             .execute(pool)
             .await
-            .map(|_| ())
+            .map(|res| res.rows_affected() > 0)
     }
 
-    async fn set_quantity(pool: &MySqlPool, shopping_cart_id: &str, product: &str, quantity: u64) -> Result<(), sqlx::Error> {
-        sqlx::query("UPDATE shopping_cart_article SET quantity = ? WHERE shopping_cart_id = ? AND product = ?")
+    /// Change the quantity, return `false` without changing it if the quantity exceeds the stock of the variant
+    async fn set_quantity(pool: &MySqlPool, shopping_cart_id: &str, variant: &str, quantity: u64) -> Result<bool, sqlx::Error> {
+        sqlx::query(
+            r#"UPDATE shopping_cart_article
+            JOIN product_variant ON product_variant.id = shopping_cart_article.variant
+            SET quantity = ?
+            WHERE shopping_cart_id = ? AND variant = ? AND ? <= product_variant.stock"#
+        )
             .bind(quantity)
             .bind(shopping_cart_id)
-            .bind(product)
+            .bind(variant)
+            .bind(quantity)
             .execute(pool)
             .await
-            .map(|_| ())
+            .map(|res| res.rows_affected() > 0)
     }
 }
 
@@ -119,24 +270,25 @@ pub(crate) mod routes {
     use std::ops::Deref;
     use axum::extract::State;
     use axum::{Form, Json};
-    use axum::response::IntoResponse;
+    use axum::http::StatusCode;
+    use axum::response::{IntoResponse, Response};
     use serde::{Deserialize, Serialize};
     use serde_json::json;
+    use sqlx::MySqlPool;
     use tracing::error;
     use crate::AppState;
-    use crate::constants::TEST_USER_ID;
-    use crate::declaration::ApiError;
+    use crate::declaration::{ApiError, unprocessable};
+    use crate::product::variant::{ProductVariant, ResolvedVariant, VariantId};
     use crate::shopping_cart::{ShoppingCart, ShoppingCartArticles};
+    use crate::shopping_cart::guest::CartOwner;
 
     /// Route: GET /shopping_cart
     ///
-    /// Get the shopping cart of the user
-    pub(crate) async fn get_shopping_cart(State(app_state): State<AppState>) -> impl IntoResponse {
-        // for now, a default user will be setup, so no authentification is required
-        // we will use the id defined in constants.rs by the constant TEST_USER_ID
+    /// Get the shopping cart of the user, or of the guest if the user is not authenticated
+    pub(crate) async fn get_shopping_cart(State(app_state): State<AppState>, owner: CartOwner) -> impl IntoResponse {
         let pool = app_state.database.get_pool().await;
 
-        let shopping_cart = match ShoppingCart::fetch_user_cart(pool.deref(), TEST_USER_ID).await {
+        let shopping_cart = match ShoppingCart::fetch_user_cart(pool.deref(), owner.0.as_str()).await {
             Ok(sc) => sc,
             Err(e) => {
                 error!(target: "GetShoppingCart", "Cannot fetch the shopping cart: {e:#?}");
@@ -149,60 +301,73 @@ pub(crate) mod routes {
             }
         };
 
-        match shopping_cart {
-            Some(sc) => Json(sc).into_response(),
-            None => {
-                let new_id = match ShoppingCart::create(pool.deref(), TEST_USER_ID).await {
-                    Ok(sc) => sc,
-                    Err(e) => {
-                        error!(target: "GetShoppingCart", "Cannot create the shopping cart: {e:#?}");
-                        return Json(
-                            ApiError {
-                                code: 5002,
-                                message: "Unable to create a new shopping cart for this user".into()
-                            }
-                        ).into_response()
-                    }
-                };
-
-                Json(
-                    ShoppingCart {
-                        id: new_id.to_string(),
-                        user: TEST_USER_ID.to_string(),
-                        articles: Vec::new()
-                    }
-                ).into_response()
-            }
-        }
+        // the cart is only stored once an article is added, a visitor only looking does not create one
+        Json(shopping_cart.unwrap_or_else(|| ShoppingCart::empty(owner.0.as_str()))).into_response()
     }
 
+    /// The variant can be omitted for a product with a single variant
     #[derive(Serialize, Deserialize, Clone)]
     pub(crate) struct ShoppingCartArticleForm {
-        product: String
+        product: String,
+        variant: Option<String>
     }
 
     #[derive(Serialize, Deserialize, Clone)]
     pub(crate) struct ChangeArticleQuantity {
         product: String,
+        variant: Option<String>,
         quantity: u64
     }
 
+    /// Find the variant of the article of a form, or build the response to return
+    async fn resolve_variant(pool: &MySqlPool, product: &str, variant: Option<&str>) -> Result<VariantId, Response> {
+        match ProductVariant::resolve(pool, product, variant).await {
+            Ok(ResolvedVariant::Found(variant)) => Ok(variant),
+            Ok(ResolvedVariant::NotFound) => Err(unprocessable(ApiError::new(5009, "This product or variant does not exist"))),
+            Ok(ResolvedVariant::Ambiguous) => Err(unprocessable(ApiError::new(5010, "This product has several variants, the variant must be given"))),
+            Err(e) => {
+                error!(target: "ResolveVariant", "Cannot find the variant of the product '{product}': {e:#?}");
+                Err(Json(ApiError::new(5011, "Unable to find the variant of the article")).into_response())
+            }
+        }
+    }
+
+    /// Build the response refusing a quantity greater than the stock of the variant
+    async fn out_of_stock(pool: &MySqlPool, product: &str, variant: &str) -> Response {
+        let stock = match ProductVariant::find(pool, product, variant).await {
+            Ok(variant) => variant.map_or(0, |v| v.stock),
+            Err(e) => {
+                error!(target: "OutOfStock", "Cannot fetch the stock of the variant '{variant}': {e:#?}");
+                0
+            }
+        };
+
+        (
+            StatusCode::CONFLICT,
+            Json(ApiError::new(5012, format!("Not enough stock, only {stock} article(s) of this variant are available")))
+        ).into_response()
+    }
+
     /// Route: POST /shopping_cart
     ///
     /// Add an article to the shopping cart
     pub(crate) async fn add_article_to_shopping_cart(
         State(app_state): State<AppState>,
+        owner: CartOwner,
         Form(form): Form<ShoppingCartArticleForm>
     ) -> impl IntoResponse
     {
-        // for now, a default user will be setup, so no authentification is required
-        // we will use the id defined in constants.rs by the constant TEST_USER_ID
         let pool = app_state.database.get_pool().await;
 
-        let shopping_cart = match ShoppingCart::fetch_user_cart(pool.deref(), TEST_USER_ID).await {
+        let variant = match resolve_variant(pool.deref(), form.product.as_str(), form.variant.as_deref()).await {
+            Ok(variant) => variant,
+            Err(res) => return res
+        };
+
+        let shopping_cart = match ShoppingCart::fetch_user_cart(pool.deref(), owner.0.as_str()).await {
             Ok(Some(sc)) => sc,
             Ok(None) => {
-                let new_id = match ShoppingCart::create(pool.deref(), TEST_USER_ID).await {
+                let new_id = match ShoppingCart::create(pool.deref(), owner.0.as_str()).await {
                     Ok(sc) => sc,
                     Err(e) => {
                         error!(target: "GetShoppingCart", "Cannot create the shopping cart: {e:#?}");
@@ -217,8 +382,9 @@ pub(crate) mod routes {
 
                 ShoppingCart {
                     id: new_id.to_string(),
-                    user: TEST_USER_ID.to_string(),
-                    articles: Vec::new()
+                    user: owner.0.clone(),
+                    articles: Vec::new(),
+                    totals: Vec::new()
                 }
             }
             Err(e) => {
@@ -236,19 +402,20 @@ pub(crate) mod routes {
         let exists = ShoppingCartArticles::exists(
             pool.deref(),
             shopping_cart.id.as_str(),
-            form.product.as_str()
+            variant.as_str()
         ).await;
 
         if exists.is_ok() {
             let res = ShoppingCartArticles::add_quantity(
                 pool.deref(),
                 shopping_cart.id.as_str(),
-                form.product.as_str(),
+                variant.as_str(),
                 1
             ).await;
 
             return match res {
-                Ok(_) => Json(json!({"message": "Quantity increased", "code": 5100})).into_response(),
+                Ok(true) => Json(json!({"message": "Quantity increased", "code": 5100})).into_response(),
+                Ok(false) => out_of_stock(pool.deref(), form.product.as_str(), variant.as_str()).await,
                 Err(e) => {
                     error!(target: "GetShoppingCart", "Cannot increase the quantity for the article: {e:#?}");
                     Json(
@@ -265,12 +432,13 @@ pub(crate) mod routes {
         let res = ShoppingCartArticles::add(
             pool.deref(),
             shopping_cart.id.as_str(),
-            form.product.as_str(),
+            variant.as_str(),
             1
         ).await;
 
         match res {
-            Ok(_) => Json(json!({"message": "Article added to the shopping cart", "code": 5101})).into_response(),
+            Ok(true) => Json(json!({"message": "Article added to the shopping cart", "code": 5101})).into_response(),
+            Ok(false) => out_of_stock(pool.deref(), form.product.as_str(), variant.as_str()).await,
             Err(e) => {
                 error!(target: "GetShoppingCart", "Cannot add the article to the shopping cart: {e:#?}");
                 Json(
@@ -285,17 +453,21 @@ pub(crate) mod routes {
 
     pub(crate) async fn edit_article_quantity(
         State(app_state): State<AppState>,
+        owner: CartOwner,
         Form(form): Form<ChangeArticleQuantity>
     ) -> impl IntoResponse
     {
-        // for now, a default user will be setup, so no authentification is required
-        // we will use the id defined in constants.rs by the constant TEST_USER_ID
         let pool = app_state.database.get_pool().await;
 
-        let shopping_cart = match ShoppingCart::fetch_user_cart(pool.deref(), TEST_USER_ID).await {
+        let variant = match resolve_variant(pool.deref(), form.product.as_str(), form.variant.as_deref()).await {
+            Ok(variant) => variant,
+            Err(res) => return res
+        };
+
+        let shopping_cart = match ShoppingCart::fetch_user_cart(pool.deref(), owner.0.as_str()).await {
             Ok(Some(sc)) => sc,
             Ok(None) => {
-                let new_id = match ShoppingCart::create(pool.deref(), TEST_USER_ID).await {
+                let new_id = match ShoppingCart::create(pool.deref(), owner.0.as_str()).await {
                     Ok(sc) => sc,
                     Err(e) => {
                         error!(target: "GetShoppingCart", "Cannot create the shopping cart: {e:#?}");
@@ -310,8 +482,9 @@ pub(crate) mod routes {
 
                 ShoppingCart {
                     id: new_id.to_string(),
-                    user: TEST_USER_ID.to_string(),
-                    articles: Vec::new()
+                    user: owner.0.clone(),
+                    articles: Vec::new(),
+                    totals: Vec::new()
                 }
             }
             Err(e) => {
@@ -329,18 +502,19 @@ pub(crate) mod routes {
         let exists = ShoppingCartArticles::exists(
             pool.deref(),
             shopping_cart.id.as_str(),
-            form.product.as_str()
+            variant.as_str()
         ).await;
         if exists.is_ok() {
             let res = ShoppingCartArticles::set_quantity(
                 pool.deref(),
                 shopping_cart.id.as_str(),
-                form.product.as_str(),
+                variant.as_str(),
                 form.quantity
             ).await;
 
             return match res {
-                Ok(_) => Json(json!({"message": "Quantity increased", "code": 5100})).into_response(),
+                Ok(true) => Json(json!({"message": "Quantity increased", "code": 5100})).into_response(),
+                Ok(false) => out_of_stock(pool.deref(), form.product.as_str(), variant.as_str()).await,
                 Err(e) => {
                     error!(target: "GetShoppingCart", "Cannot increase the quantity for the article: {e:#?}");
                     Json(
@@ -357,12 +531,13 @@ pub(crate) mod routes {
         let res = ShoppingCartArticles::add(
             pool.deref(),
             shopping_cart.id.as_str(),
-            form.product.as_str(),
+            variant.as_str(),
             form.quantity
         ).await;
 
         match res {
-            Ok(_) => Json(json!({"message": "Article added to the shopping cart", "code": 5102})).into_response(),
+            Ok(true) => Json(json!({"message": "Article added to the shopping cart", "code": 5102})).into_response(),
+            Ok(false) => out_of_stock(pool.deref(), form.product.as_str(), variant.as_str()).await,
             Err(e) => {
                 error!(target: "GetShoppingCart", "Cannot add the article to the shopping cart: {e:#?}");
                 Json(
@@ -377,35 +552,21 @@ pub(crate) mod routes {
 
     pub(crate) async fn delete_article(
         State(app_state): State<AppState>,
+        owner: CartOwner,
         Form(form): Form<ShoppingCartArticleForm>
     ) -> impl IntoResponse
     {
-        // for now, a default user will be setup, so no authentification is required
-        // we will use the id defined in constants.rs by the constant TEST_USER_ID
         let pool = app_state.database.get_pool().await;
 
-        let shopping_cart = match ShoppingCart::fetch_user_cart(pool.deref(), TEST_USER_ID).await {
-            Ok(Some(sc)) => sc,
-            Ok(None) => {
-                let new_id = match ShoppingCart::create(pool.deref(), TEST_USER_ID).await {
-                    Ok(sc) => sc,
-                    Err(e) => {
-                        error!(target: "GetShoppingCart", "Cannot create the shopping cart: {e:#?}");
-                        return Json(
-                            ApiError {
-                                code: 5003,
-                                message: "Unable to create a new shopping cart for this user".into()
-                            }
-                        ).into_response()
-                    }
-                };
+        let variant = match resolve_variant(pool.deref(), form.product.as_str(), form.variant.as_deref()).await {
+            Ok(variant) => variant,
+            Err(res) => return res
+        };
 
-                ShoppingCart {
-                    id: new_id.to_string(),
-                    user: TEST_USER_ID.to_string(),
-                    articles: Vec::new()
-                }
-            }
+        let shopping_cart = match ShoppingCart::fetch_user_cart(pool.deref(), owner.0.as_str()).await {
+            Ok(Some(sc)) => sc,
+            // without a stored cart there is nothing to delete
+            Ok(None) => return Json(json!({"message": "Article deleted from the shopping cart", "code": 5103})).into_response(),
             Err(e) => {
                 error!(target: "GetShoppingCart", "Cannot fetch the shopping cart: {e:#?}");
                 return Json(
@@ -420,7 +581,7 @@ pub(crate) mod routes {
         let res = ShoppingCartArticles::delete(
             pool.deref(),
             shopping_cart.id.as_str(),
-            form.product.as_str()
+            variant.as_str()
         ).await;
 
         match res {
@@ -438,35 +599,16 @@ pub(crate) mod routes {
     }
 
     pub(crate) async fn clear_articles(
-        State(app_state): State<AppState>
+        State(app_state): State<AppState>,
+        owner: CartOwner
     ) -> impl IntoResponse
     {
-        // for now, a default user will be setup, so no authentification is required
-        // we will use the id defined in constants.rs by the constant TEST_USER_ID
         let pool = app_state.database.get_pool().await;
 
-        let shopping_cart = match ShoppingCart::fetch_user_cart(pool.deref(), TEST_USER_ID).await {
+        let shopping_cart = match ShoppingCart::fetch_user_cart(pool.deref(), owner.0.as_str()).await {
             Ok(Some(sc)) => sc,
-            Ok(None) => {
-                let new_id = match ShoppingCart::create(pool.deref(), TEST_USER_ID).await {
-                    Ok(sc) => sc,
-                    Err(e) => {
-                        error!(target: "GetShoppingCart", "Cannot create the shopping cart: {e:#?}");
-                        return Json(
-                            ApiError {
-                                code: 5003,
-                                message: "Unable to create a new shopping cart for this user".into()
-                            }
-                        ).into_response()
-                    }
-                };
-
-                ShoppingCart {
-                    id: new_id.to_string(),
-                    user: TEST_USER_ID.to_string(),
-                    articles: Vec::new()
-                }
-            }
+            // without a stored cart there is nothing to delete
+            Ok(None) => return Json(json!({"message": "Articles deleted from the shopping cart", "code": 5104})).into_response(),
             Err(e) => {
                 error!(target: "GetShoppingCart", "Cannot fetch the shopping cart: {e:#?}");
                 return Json(
@@ -25,16 +25,11 @@ addEventListener("load", () => {
 })
 
 async function update_cart_price(cart){
-    let products = await Promise.all(
-        cart.articles
-            .map(async(p) => ( { quantity: p.quantity, ...(await api.get_product(p.product)) }) || { price: 0, quantity: -1 })
-    );
-    console.log(products);
-    let price = products.reduce((a,b) => a += b.price * b.quantity, 0);
+    let price = cart.totals.map((total) => total.formatted).join(" + ") || "0,00 €";
     document.getElementById("price")
-        .textContent = `${price}€`;
+        .textContent = price;
     document.getElementById("total_price")
-        .textContent = `${price}€`;
+        .textContent = price;
 }
 
 function clear_container(){
@@ -55,16 +50,17 @@ async function add_product(product_informations, id) {
         throw new Error("Cannot fetch the product")
 
     let style = "";
-    if (product.main_image) {
-        style = `style="background: center url('static/img/${product.main_image}')"`
+    if (product.images.length > 0) {
+        style = `style="background: center url('${api.config.host}${product.images[0].sizes[0].webp_url}')"`
     }
     console.log(product_informations, product)
     let elm = `<div class="product">
                         <div class="img" ${style}></div>
                         <div class="infos">
                             <h2>${product.name}</h2>
+                            ${product_informations.variant_label ? `<p>${product_informations.variant_label}</p>` : ''}
                             <p>Quantité : ${product_informations.quantity}</p>
-                            <h3>${product.price}€${product_informations.quantity > 1 ? ` x${product_informations.quantity} (${product.price * product_informations.quantity}€)` : ''}</h3>
+                            <h3>${product.price.formatted}${product_informations.quantity > 1 ? ` x${product_informations.quantity}` : ''}</h3>
                         </div>
                         <div class="remove" id="product_${id}_remove">
                             <p>Supprimer</p>
@@ -86,7 +82,7 @@ async function add_product(product_informations, id) {
             "click",
             () => {
                 // send a request to delete the ressources
-                api.remove_cart_article(product_informations.product)
+                api.remove_cart_article(product_informations.product, product_informations.variant)
                 document.location.reload();
             }
         )
@@ -39,7 +39,7 @@ class PedalizeApi {
      *
      * @param {number} page - The page number to retrieve. Must be a positive or null integer.
      *
-     * @returns {Promise<Option<Product[]>>} - A promise that resolves to an Option containing an array of Product objects on success, or an error on failure.
+     * @returns {Promise<Option<Page<Product>>>} - A promise that resolves to an Option containing a page of Product objects on success, or an error on failure.
      *
      * @throws {Error} - If the page number is not a positive or null integer.
      */
@@ -98,13 +98,33 @@ class PedalizeApi {
             }
         });
     }
+    /**
+     * Retrieves the options and the variants of a product specified by its ID.
+     *
+     * @param {string} id - The ID of the product.
+     * @returns {Promise<Option<ProductVariants>>} - A promise that resolves to the options and the variants, or an error.
+     */
+    get_product_variants(id) {
+        return __awaiter(this, void 0, void 0, function* () {
+            const req = new RequestBuilder(`${this.config.host}/product/${id}/variants`);
+            req.set_method("GET");
+            try {
+                const res = yield req.send();
+                return yield res.json();
+            }
+            catch (error) {
+                console.error(error);
+                return error;
+            }
+        });
+    }
     /**
      * Retrieves the reviews for a given product ID.
      *
      * @param {string} id - The ID of the product to retrieve reviews for.
      *
-     * @returns {Promise<Option<Review[]>>} - A promise that resolves to an optional array of Review objects.
-     *       The array will be empty if no reviews are found.
+     * @returns {Promise<Option<Page<Review>>>} - A promise that resolves to an optional page of Review objects, the most recent first.
+     *       Its items will be empty if no reviews are found.
      *       If an error occurs during the retrieval process, the promise will reject with the error.
      */
     get_reviews(id) {
@@ -121,6 +141,50 @@ class PedalizeApi {
             }
         });
     }
+    /**
+     * Retrieves the number of reviews per star value of a product, their mean and their total.
+     *
+     * @param {string} id - The ID of the product.
+     *
+     * @returns {Promise<Option<ReviewSummary>>} - A promise that resolves to the summary, or an error.
+     */
+    get_review_summary(id) {
+        return __awaiter(this, void 0, void 0, function* () {
+            const req = new RequestBuilder(`${this.config.host}/product/${id}/reviews/summary`);
+            req.set_method("GET");
+            try {
+                const res = yield req.send();
+                return yield res.json();
+            }
+            catch (error) {
+                console.error(error);
+                return error;
+            }
+        });
+    }
+    /**
+     * Chooses a new password with the token of a reset link.
+     *
+     * @param {string} token - The token given by the reset link.
+     * @param {string} password - The new password.
+     *
+     * @returns {Promise<Object>} - A promise that resolves to the response of the server, or an error.
+     */
+    reset_password(token, password) {
+        return __awaiter(this, void 0, void 0, function* () {
+            const req = new RequestBuilder(`${this.config.host}/clients/password/reset`);
+            req.set_method("POST");
+            req.set_body(new URLSearchParams({ token, password }));
+            try {
+                const res = yield req.send();
+                return yield res.json();
+            }
+            catch (error) {
+                console.error(error);
+                return error;
+            }
+        });
+    }
     /**
      * Retrieves the shopping cart from the server.
      *
@@ -141,13 +205,11 @@ class PedalizeApi {
             }
         });
     }
-    add_article_to_cart(id) {
+    add_article_to_cart(id, variant) {
         return __awaiter(this, void 0, void 0, function* () {
             const req = new RequestBuilder(`${this.config.host}/shopping_cart/articles/add`);
             req.set_method("POST");
-            req.set_body(new URLSearchParams({
-                product: id
-            }));
+            req.set_body(new URLSearchParams(Object.assign({ product: id }, (variant ? { variant } : {}))));
             try {
                 const res = yield req.send();
                 return yield res.text();
@@ -158,13 +220,11 @@ class PedalizeApi {
             }
         });
     }
-    remove_cart_article(id) {
+    remove_cart_article(id, variant) {
         return __awaiter(this, void 0, void 0, function* () {
             const req = new RequestBuilder(`${this.config.host}/shopping_cart/articles/remove`);
             req.set_method("DELETE");
-            req.set_body(new URLSearchParams({
-                product: id
-            }));
+            req.set_body(new URLSearchParams(Object.assign({ product: id }, (variant ? { variant } : {}))));
             //req.add_header("Content-Type:", "application/x-www-form-urlencoded")
             console.log(req);
             try {
@@ -234,6 +294,8 @@ class RequestBuilder {
         let config = {
             method: this.method || "GET",
             body: this.body || null,
+            // send the session and guest cookies to the API
+            credentials: "include",
         };
         if (this.headers) {
             config.headers = this.headers;
@@ -53,9 +53,9 @@ function add_article(product) {
     let list = document.getElementById("product_list");
 
     let style = "";
-    console.log(product, product.main_image)
-    if (product.main_image) {
-        style = `background: center no-repeat url('static/img/${product.main_image}')`
+    console.log(product, product.images)
+    if (product.images.length > 0) {
+        style = `background: center no-repeat url('${api.config.host}${product.images[0].sizes[0].webp_url}')`
     }
     console.log(style)
 
@@ -63,7 +63,8 @@ function add_article(product) {
         <div class="product">
             <div class="img" style="${style}"></div>
             <h2>${product.name}</h2>
-            <p>${product.price}€</p>
+            <p>${product.price.formatted}</p>
+            ${product.review_count > 0 ? `<p>${product.average_stars.toFixed(1)} ★ (${product.review_count} avis)</p>` : ''}
         </div>
     </a>`
 }
\ No newline at end of file
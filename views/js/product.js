@@ -1,5 +1,8 @@
 let api;
 let product_id;
+// options and variants of the product, and the value chosen for each option
+let product_variants = { options: [], variants: [] };
+let chosen_values = {};
 
 import * as client from './client.js';
 
@@ -18,6 +21,14 @@ window.addEventListener("load", () => {
         console.error
     )
 
+    api.get_product_variants(product_id).then(
+        (v) => {
+            if (v && v.variants)
+                write_variant_options(v)
+        },
+        console.error
+    )
+
 })
 
 
@@ -28,54 +39,32 @@ window.addEventListener("load", () => {
  * @param {string}   product.id          - The unique identifier of the product.
  * @param {string}   product.name        - The name of the product.
  * @param {Option<string>}   product.description - Optional. The description of the product.
- * @param {number}   product.price       - The price of the product.
- * @param {boolean}   product.size       - The size of the product.
- * @param {boolean}   product.wheel      - The wheel of the product.
- * @param {Option<string>}   product.main_image       - Optional. The URL of the product image.
- * @param {Option<string>}   product.second_image       - Optional. The URL of the product image.
- * @param {Option<string>}   product.third_image       - Optional. The URL of the product image.
- * @param {Option<string>}   product.fourth_image       - Optional. The URL of the product image.
+ * @param {Money}    product.price       - The price of the product, in cents and formatted.
+ * @param {ProductImage[]}   product.images       - The gallery of the product, in display order.
  */
 function write_new_product_informations(product){
     console.log(product)
+    // the widest resized version for the main image, the narrowest for the others
+    const image_url = (image, widest) => `${api.config.host}${image.sizes[widest ? image.sizes.length - 1 : 0].webp_url}`;
     // update name
     document.getElementById("product_name").textContent = product.name;
 
-    if (!product.size) {
-        document.querySelector(".taille").style["display"] = "none";
-    } else {
-        delete document.querySelector(".taille").style["display"];
-
-    }
-    if (!product.wheel) {
-        document.querySelector(".wheel_size").style["display"] = "none";
-    } else {
-        delete document.querySelector(".wheel_size").style["display"];
-    }
-
+    const [main_image, ...others] = product.images;
     document.querySelector(".main_image img")
-        .src = product.main_image ? `static/img/${product.main_image}` : "static/img/no-photo.png";
-
-    if (product.second_image) {
-        document.querySelector(".second_image").src = `static/img/${product.second_image}`
-    } else {
-        let child = document.querySelector(".second_image");
-        child.parentNode.removeChild(child)
+        .src = main_image ? image_url(main_image, true) : "static/img/no-photo.png";
+    if (main_image && main_image.alt) {
+        document.querySelector(".main_image img").alt = main_image.alt;
     }
 
-    if (product.third_image) {
-        document.querySelector(".third_image").src = `static/img/${product.third_image}`
-    } else {
-        let child = document.querySelector(".third_image");
-        child.parentNode.removeChild(child)
-    }
-
-    if (product.fourth_image) {
-        document.querySelector(".fourth_image").src = `static/img/${product.fourth_image}`
-    } else {
-        let child = document.querySelector(".fourth_image");
-        child.parentNode.removeChild(child)
-    }
+    ["second_image", "third_image", "fourth_image"].forEach((name, i) => {
+        let elm = document.querySelector(`.${name}`);
+        if (others[i]) {
+            elm.src = image_url(others[i], false);
+            elm.alt = others[i].alt || "";
+        } else {
+            elm.parentNode.removeChild(elm)
+        }
+    });
 
     document.querySelector(".description")
         .textContent = product.description || "Aucune description n'a été fournie."
@@ -90,6 +79,69 @@ function write_new_product_informations(product){
         )
 }
 
+/**
+ * Writes one list of choices per option of the product, the values of the first variant in stock are chosen.
+ *
+ * @param {ProductVariants} variants - The options of the product and all its variants.
+ */
+function write_variant_options(variants){
+    product_variants = variants;
+
+    const first = variants.variants.find((v) => v.stock > 0) || variants.variants[0];
+    chosen_values = first ? { ...first.values } : {};
+
+    const container = document.getElementById("variant_options");
+    container.innerHTML = "";
+
+    variants.options.forEach((option) => {
+        const elm = document.createElement("div");
+        elm.className = "variant_option";
+        elm.innerHTML = `<h2>${option.name}</h2><br><section class="frame_size"></section>`;
+
+        option.values.forEach((value) => {
+            const box = document.createElement("div");
+            box.className = "box_frame_size";
+            box.innerHTML = `<p class="size">${value}</p>`;
+            box.addEventListener("click", () => {
+                chosen_values[option.name] = value;
+                update_variant_options();
+            });
+            box.dataset.option = option.name;
+            box.dataset.value = value;
+            elm.querySelector("section").appendChild(box);
+        });
+
+        container.appendChild(elm);
+    });
+
+    update_variant_options();
+}
+
+/**
+ * Highlights the chosen values, and greys out the values whose variant is out of stock.
+ */
+function update_variant_options(){
+    document.querySelectorAll("#variant_options .box_frame_size").forEach((box) => {
+        const values = { ...chosen_values, [box.dataset.option]: box.dataset.value };
+        const variant = find_variant(values);
+
+        box.classList.toggle("selected", chosen_values[box.dataset.option] === box.dataset.value);
+        box.classList.toggle("out_of_stock", !variant || variant.stock < 1);
+    });
+}
+
+/**
+ * Finds the variant having exactly the given value for each option.
+ *
+ * @param {Object<string, string>} values - The value of each option.
+ * @return {Option<ProductVariant>} The matching variant, or undefined.
+ */
+function find_variant(values){
+    return product_variants.variants.find(
+        (v) => product_variants.options.every((o) => v.values[o.name] === values[o.name])
+    );
+}
+
 /**
  * Updates the product characteristics display on the page.
  *
@@ -157,7 +209,13 @@ function add_event_listeners(){
  * @void
  */
 function add_to_cart(){
-    api.add_article_to_cart(product_id).then(
+    const variant = find_variant(chosen_values);
+    if (!variant)
+        return alert("Cette combinaison n'est pas proposée pour ce produit.");
+    if (variant.stock < 1)
+        return alert("Cette version du produit est en rupture de stock.");
+
+    api.add_article_to_cart(product_id, variant.id).then(
         () => {
             window.location.href = "./cart.html";
         },
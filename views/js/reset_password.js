@@ -0,0 +1,35 @@
+import * as client from './client.js';
+
+let api;
+
+addEventListener("load", () => {
+    // TODO N'oubliez pas de changer cette variable!
+    api = new client.PedalizeApi('http://127.0.0.1:9999');
+
+    // the token comes from the link sent by email
+    let token = new URLSearchParams(window.location.search).get("token");
+    if (!token)
+        return show_message("Ce lien de réinitialisation est incomplet.");
+
+    document.getElementById("reset_password").addEventListener("submit", (e) => {
+        e.preventDefault();
+
+        let password = document.getElementById("password").value;
+        if (password !== document.getElementById("confirmation").value)
+            return show_message("Les deux mots de passe ne correspondent pas.");
+
+        api.reset_password(token, password).then(
+            (res) => {
+                if (res && res.code === 6106)
+                    return show_message("Votre mot de passe a été changé, vous pouvez vous connecter.");
+
+                show_message(res && res.message ? res.message : "Impossible de changer le mot de passe.");
+            },
+            console.error
+        )
+    })
+})
+
+function show_message(message) {
+    document.getElementById("message").textContent = message;
+}